@@ -79,6 +79,63 @@ CREATE TABLE IF NOT EXISTS UsersChatLogCharacter
     character_id INTEGER NOT NULL REFERENCES Characters(character_id)
 );
 
+/*
+    One row per login or logout seen on the friends list. A character's current status is their latest row.
+*/
+CREATE TABLE IF NOT EXISTS FriendPresence
+(
+    friend_presence_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    character_id INTEGER NOT NULL REFERENCES Characters(character_id),
+    online BOOLEAN NOT NULL,
+    timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS FriendPresence_character_id ON FriendPresence(character_id);
+
+/*
+    Panics reported by the injected capture payload. game_checksum is the swtor.exe build it was running in.
+*/
+CREATE TABLE IF NOT EXISTS CaptureIncidents
+(
+    capture_incident_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    message TEXT NOT NULL,
+    game_checksum VARCHAR(64),
+    timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+/*
+    Posts waiting for their time. post_at is an RFC 3339 UTC timestamp and character_message is the json of a
+    UserCharacterMessages. Rows are removed once they have been handed to the post queue.
+*/
+CREATE TABLE IF NOT EXISTS ScheduledPosts
+(
+    scheduled_post_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    post_at VARCHAR(64) NOT NULL,
+    retry BOOLEAN NOT NULL,
+    character_message TEXT NOT NULL
+);
+
+/*
+    Named lists of characters a whisper can be broadcast to. recipients is a json array of character names.
+*/
+CREATE TABLE IF NOT EXISTS RecipientGroups
+(
+    recipient_group_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_name VARCHAR(255) UNIQUE NOT NULL,
+    recipients TEXT NOT NULL
+);
+
+/*
+    Saved post macros, one step per line of source. They're parsed whenever they're run, so a macro that no
+    longer parses can still be saved and fixed later.
+*/
+CREATE TABLE IF NOT EXISTS PostMacros
+(
+    post_macro_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    macro_name VARCHAR(255) UNIQUE NOT NULL,
+    source TEXT NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS DB_Version
 (
     db_version_id INTEGER PRIMARY KEY AUTOINCREMENT,
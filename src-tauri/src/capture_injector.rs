@@ -1,41 +1,78 @@
 
-use std::{io::{ErrorKind, Read, Write}, net::{TcpListener, TcpStream}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, time::Duration};
+use std::{io::{ErrorKind, Read}, net::{TcpListener, TcpStream}, sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex}, time::{Duration, Instant}};
 use std::thread;
 
-use tracing::{error, info};
+use tracing::{error, info, info_span, warn};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::{Deserializer, Value};
 use dll_syringe::{process::OwnedProcess, Syringe};
-use chator_macros::sha256_to_array;
+use tokio::task;
 
 use crate::{share::CaptureMessage, swtor_hook};
+use crate::share::auth::FrameError;
+use crate::share::codec::FrameDecoder;
+use crate::share::control::{ControlMessage, ControlReply};
+use crate::share::handshake::HookOffsets;
+use crate::share::signature::HookSignatures;
+use crate::share::session::CaptureSession;
+use crate::game_signatures::SignatureManifest;
+use crate::dal::db::settings;
 use crate::dal::db::swtor_message::SwtorMessage;
+use crate::dal::db::friend_presence::FriendStatus;
+use crate::dal::db::capture_incident::CaptureIncident;
 
 pub mod message_container;
+pub mod control;
+pub mod health;
+pub mod recorder;
+pub mod state;
+pub mod auto_capture;
 use self::message_container::SwtorMessageContainer;
-
-const SUPPORTED_SWTOR_CHECKSUM: [u8; 32] = sha256_to_array!("9999679ECF122DF9B3E460B2C85E1FBE46F891E38841AF6A38CD79895F46D6D9");
+use self::health::CaptureHealth;
+use self::state::CaptureState;
 
 lazy_static! {
-    static ref INJECTED: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
-    static ref CONTINUE_LOGGING: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
     static ref MESSAGE_CONTAINER: Arc<Mutex<SwtorMessageContainer>> = Arc::new(Mutex::new(SwtorMessageContainer::new()));
+    static ref CAPTURED_PID: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(None));
 }
 
-#[derive(Deserialize, Serialize)]
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Tracing target for everything the payload reports about itself, so it can be told apart from our own logs.
+pub const PAYLOAD_LOG_TARGET: &str = "capture_payload";
+
+/// A CaptureError reported by the payload, emitted as capture_notification.
+#[derive(Serialize, Clone)]
+pub struct CaptureNotification {
+    pub message: String,
+    pub received_at: DateTime<Utc>
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub enum CaptureError {
     AlreadyInjected,
     SwtorNotRunning,
     WrongGuiSettings,
     UnsupportedVersion,
-    NotYetFullyReady
+    NotYetFullyReady,
+    ManifestUnavailable,
+    SessionUnavailable,
+    PortUnavailable,
+    NotInjected,
+    ControlChannelClosed,
+    ControlTimeout,
+    ControlRejected(String),
+    MismatchedPayload {
+        crate_version: Option<String>,
+        protocol_version: Option<u32>
+    }
 }
 
 #[tauri::command]
 pub fn start_injecting_capture(window: tauri::Window) -> Result<(), CaptureError> {
 
-    if INJECTED.load(Ordering::Relaxed) {
+    if state::is_active() {
         return Err(CaptureError::AlreadyInjected);
     }
 
@@ -45,31 +82,147 @@ pub fn start_injecting_capture(window: tauri::Window) -> Result<(), CaptureError
     }
     let swtor_pid = swtor_pid.unwrap();
 
-    match swtor_hook::checksum_match(&SUPPORTED_SWTOR_CHECKSUM) {
-        Ok(true) => {},
-        Ok(false) => return Err(CaptureError::UnsupportedVersion),
-        Err(_) => return Err(CaptureError::NotYetFullyReady)
-    }
+    let (offsets, signatures) = find_hook_targets()?;
 
-    start_injecting_thread(swtor_pid, window);
+    // Claimed before the session file is written, so a start that loses a race can't replace a live session.
+    state::begin(window.clone(), CaptureState::Injecting)?;
+    let (listener, secret) = match open_capture_session(offsets, signatures) {
+        Ok(opened) => opened,
+        Err(err) => {
+            state::finish();
+            return Err(err);
+        }
+    };
+    CAPTURED_PID.lock().unwrap().replace(swtor_pid);
+    start_injecting_thread(swtor_pid, window, listener, secret);
     return Ok(());
 
 }
 
-fn start_injecting_thread(swtor_pid: u32, window: tauri::Window) {
+/*
+    Listens exactly like a real capture, but nothing is injected. Used together with the capture_simulator
+    binary to exercise the capture pipeline without the game.
+*/
+#[tauri::command]
+pub fn start_simulated_capture(window: tauri::Window) -> Result<(), CaptureError> {
+
+    if state::is_active() {
+        return Err(CaptureError::AlreadyInjected);
+    }
+
+    state::begin(window.clone(), CaptureState::Handshaking)?;
+    let (listener, secret) = match open_capture_session(HookOffsets::default(), HookSignatures::default()) {
+        Ok(opened) => opened,
+        Err(err) => {
+            state::finish();
+            return Err(err);
+        }
+    };
+    CAPTURED_PID.lock().unwrap().take();
+
+    thread::spawn(move || {
+
+        health::start_health_monitor(window.clone());
+        start_logging_propagation(window.clone());
+        info!("Waiting for a simulated payload");
+        start_tcp_listener_loop(listener, window, secret);
+
+        state::finish();
+
+    });
+
+    Ok(())
+
+}
+
+/// The game process the current or last capture was injected into. None for simulated captures and replays.
+pub fn captured_pid() -> Option<u32> {
+
+    CAPTURED_PID.lock().unwrap().clone()
+
+}
+
+fn find_hook_targets() -> Result<(HookOffsets, HookSignatures), CaptureError> {
+
+    let checksum = swtor_hook::get_checksum_hex()
+        .map_err(|_| CaptureError::NotYetFullyReady)?;
+
+    let manifest = SignatureManifest::load()
+        .map_err(|err| {
+            error!("{}", err);
+            CaptureError::ManifestUnavailable
+        })?;
+
+    match manifest.find_build(&checksum) {
+        Some(build) => Ok((build.offsets.clone(), manifest.signatures)),
+        None if manifest.signatures.chat.is_some() => {
+            info!("No offsets for swtor.exe with checksum {}, falling back to signature scanning", checksum);
+            Ok((HookOffsets::default(), manifest.signatures))
+        },
+        None => {
+            warn!("No game signatures for swtor.exe with checksum {}", checksum);
+            Err(CaptureError::UnsupportedVersion)
+        }
+    }
+
+}
+
+fn open_capture_session(offsets: HookOffsets, signatures: HookSignatures) -> Result<(TcpListener, String), CaptureError> {
+
+    // Let the OS pick the port, so a second instance or another program can't collide with us.
+    let (listener, capture_port) = match bind_capture_listener() {
+        Ok(bound) => bound,
+        Err(err) => {
+            error!("Error binding capture listener: {:?}", err);
+            return Err(CaptureError::PortUnavailable);
+        }
+    };
+
+    // A fresh secret per injection, so a payload or peer from an earlier session can't talk to this one.
+    let session = CaptureSession::generate(capture_port, offsets, signatures, settings::get_settings().chat_log.capture_queue)
+        .and_then(|session| session.write().map(|_| session));
+
+    let session = match session {
+        Ok(session) => session,
+        Err(err) => {
+            error!("{}", err);
+            return Err(CaptureError::SessionUnavailable);
+        }
+    };
+
+    Ok((listener, session.secret))
+
+}
+
+fn remove_capture_session() {
+
+    if let Err(err) = CaptureSession::remove() {
+        error!("{}", err);
+    }
+
+}
+
+fn bind_capture_listener() -> Result<(TcpListener, u16), std::io::Error> {
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    Ok((listener, port))
+
+}
+
+fn start_injecting_thread(swtor_pid: u32, window: tauri::Window, listener: TcpListener, secret: String) {
 
     thread::spawn(move || {
 
-        INJECTED.store(true, Ordering::Relaxed);
-        CONTINUE_LOGGING.store(true, Ordering::Relaxed);
-        
         let target_process = OwnedProcess::from_pid(swtor_pid).unwrap();
         let syringe = Syringe::for_process(target_process);
 
-        let tcp_thread = thread::spawn(|| {
-            start_tcp_listener_loop();
+        let listener_window = window.clone();
+        let tcp_thread = thread::spawn(move || {
+            start_tcp_listener_loop(listener, listener_window, secret);
         });
         thread::sleep(Duration::from_secs(1));
+        health::start_health_monitor(window.clone());
         start_logging_propagation(window);
 
         let injected_payload = if cfg!(debug_assertions) {
@@ -84,80 +237,257 @@ fn start_injecting_thread(swtor_pid: u32, window: tauri::Window) {
             },
             Err(err) => {
                 error!("Error injecting payload: {:?}", err);
-                INJECTED.store(false, Ordering::Relaxed);
-                CONTINUE_LOGGING.store(false, Ordering::Relaxed);
+                state::fail(format!("Error injecting payload: {:?}", err));
+                tcp_thread.join().unwrap();
+                state::finish();
                 return;
             }
         }
 
         let injected_payload = injected_payload.unwrap();
+        state::advance_from(CaptureState::Injecting, CaptureState::Handshaking);
 
         tcp_thread.join().unwrap();
+        state::advance(CaptureState::Ejecting);
 
         if let Err(err) = syringe.eject(injected_payload) {
             error!("Error ejecting payload: {:?}", err);
         } else {
             info!("Payload ejected");
         }
-        CONTINUE_LOGGING.store(false, Ordering::Relaxed);
-        INJECTED.store(false, Ordering::Relaxed);
+        state::finish();
 
     });
 
 }
 
-fn start_tcp_listener_loop() {
+/*
+    Connections are read one at a time, so a peer that connects and never sends a signed Hello would otherwise
+    keep the payload from ever getting through.
+*/
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
 
-    let listener = TcpListener::bind("127.0.0.1:4592").unwrap();
-    let mut stream = listener.accept().unwrap().0;
+enum StreamEnd {
+    Unauthenticated,
+    Finished
+}
 
-    stream.set_read_timeout(Some(Duration::from_millis(1000))).unwrap();
+fn start_tcp_listener_loop(listener: TcpListener, window: tauri::Window, secret: String) {
+
+    /* Everything logged for this capture, including what the payload forwards, is grouped under one span. */
+    let session_id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+    let span = info_span!("capture_session", id = session_id, pid = ?captured_pid());
+    let _entered = span.enter();
+
+    listener.set_nonblocking(true).unwrap();
+
+    if settings::get_settings().chat_log.record_capture_stream {
+        recorder::start();
+    }
 
     info!("Listening for messages");
+    while state::is_live() {
+
+        let (stream, peer) = match listener.accept() {
+            Ok(connection) => connection,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            },
+            Err(err) => {
+                error!("Error accepting capture connection: {:?}", err);
+                state::fail(format!("Error accepting capture connection: {}", err));
+                break;
+            }
+        };
+
+        match read_capture_stream(stream, &window, &secret) {
+            StreamEnd::Unauthenticated => {
+                warn!("Rejected unauthenticated capture connection from {}", peer);
+            },
+            StreamEnd::Finished => {
+                break;
+            }
+        }
+
+    }
+    info!("Stopped listening for messages");
+    recorder::stop();
+    control::detach();
+    remove_capture_session();
+    thread::sleep(Duration::from_secs(1));
+
+}
+
+fn read_capture_stream(mut stream: TcpStream, window: &tauri::Window, secret: &str) -> StreamEnd {
+
+    stream.set_nonblocking(false).unwrap();
+    stream.set_read_timeout(Some(Duration::from_millis(1000))).unwrap();
+
+    let mut decoder = FrameDecoder::new();
+    let mut handshaken = false;
+    let mut quit_request: Option<u64> = None;
+    let mut quit_deadline = Instant::now();
+    let handshake_deadline = Instant::now() + HANDSHAKE_TIMEOUT;
     let mut buffer: [u8; 2048] = [0; 2048];
-    while CONTINUE_LOGGING.load(Ordering::Relaxed) {
+    loop {
+
+        if !handshaken && Instant::now() > handshake_deadline {
+            warn!("No handshake from the capture connection within {:?}", HANDSHAKE_TIMEOUT);
+            return StreamEnd::Unauthenticated;
+        }
+
+        /*
+            Once asked to stop, tell the payload to remove its detours and keep reading until it acknowledges,
+            so the payload isn't ejected with a detour still in place.
+        */
+        if !state::is_live() {
+
+            if !handshaken {
+                return StreamEnd::Finished;
+            }
+
+            match quit_request {
+                None => {
+                    match control::send(ControlMessage::Quit) {
+                        Ok(id) => {
+                            quit_request  = Some(id);
+                            quit_deadline = Instant::now() + control::DEFAULT_REPLY_TIMEOUT;
+                        },
+                        Err(_) => return StreamEnd::Finished
+                    }
+                },
+                Some(id) => {
+                    if control::take_reply(id).is_some() || Instant::now() > quit_deadline {
+                        return StreamEnd::Finished;
+                    }
+                }
+            }
+
+        }
 
         match stream.read(&mut buffer) {
-            Ok(_) => {},
+            Ok(0) => {
+                info!("Capture stream closed by the payload");
+                return StreamEnd::Finished;
+            },
+            Ok(read) => {
+                decoder.push(&buffer[..read]);
+            },
             Err(ref e) if e.kind() == ErrorKind::TimedOut || e.kind() == ErrorKind::WouldBlock => {
                 continue;
             },
             Err(err) => {
                 error!("Error reading from stream: {:?}", err);
-                break;
+                return StreamEnd::Finished;
             }
         }
 
-        Deserializer::from_slice(&buffer).into_iter::<Value>().for_each(|value| {
+        while let Some(frame) = decoder.next_frame() {
+
+            // Past a bad length there's no telling where the next frame starts, so the connection is dropped.
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(err) if !handshaken => {
+                    warn!("Capture stream out of sync before handshake: {:?}", err);
+                    return StreamEnd::Unauthenticated;
+                },
+                Err(err) => {
+                    error!("Capture stream out of sync: {:?}", err);
+                    state::fail(format!("Capture stream out of sync: {:?}", err));
+                    return StreamEnd::Finished;
+                }
+            };
 
-            if let Ok(value) = value {
+            let message = CaptureMessage::from_frame(secret, &frame);
 
-                if let Ok(message) = serde_json::from_value(value) {
+            if let Err(FrameError::Unauthenticated) = message {
+                return StreamEnd::Unauthenticated;
+            }
+
+            if let Ok(message) = &message {
+                recorder::record(message);
+            }
 
-                    handle_message(message);
+            if !handshaken {
 
+                if let Err(capture_error) = verify_handshake(message) {
+                    error!("Refusing capture payload: {:?}", capture_error);
+                    window.emit("capture_error", capture_error.clone()).unwrap();
+                    state::fail(format!("{:?}", capture_error));
+                    return StreamEnd::Finished;
                 }
+                handshaken = true;
+                control::attach(&stream, secret);
+                health::reset();
+                state::advance(CaptureState::Running);
+                continue;
 
             }
 
-        });
-        buffer = [0; 2048];
+            match message {
+                Ok(message) => handle_message(window, message),
+                Err(err) => error!("Error decoding capture message: {:?}", err)
+            }
+
+        }
+
     }
-    info!("Stopped listening for messages");
 
-    if let Ok(mut stream) = TcpStream::connect("127.0.0.1:4593") {
-        stream.write(b"stop").unwrap();
+}
+
+/*
+    The first frame from the payload must be a Hello from the same build as the app. Anything else is most
+    likely a DLL left over from an older install that find_or_inject picked up instead of injecting ours.
+*/
+fn verify_handshake(message: Result<CaptureMessage, FrameError>) -> Result<(), CaptureError> {
+
+    match message {
+        Ok(CaptureMessage::Hello(hello)) if hello.is_compatible() => {
+            info!("Handshake with payload {} (protocol {}), offsets {:?}", hello.crate_version, hello.protocol_version, hello.offsets);
+            Ok(())
+        },
+        Ok(CaptureMessage::Hello(hello)) => {
+            Err(CaptureError::MismatchedPayload {
+                crate_version: Some(hello.crate_version),
+                protocol_version: Some(hello.protocol_version)
+            })
+        },
+        _ => {
+            Err(CaptureError::MismatchedPayload {
+                crate_version: None,
+                protocol_version: None
+            })
+        }
     }
-    thread::sleep(Duration::from_secs(1));
 
 }
 
-fn handle_message(message: CaptureMessage) {
+fn handle_message(window: &tauri::Window, message: CaptureMessage) {
 
     match message {
 
+        CaptureMessage::Info(info) => {
+            info!(target: PAYLOAD_LOG_TARGET, "{}", info);
+        },
+        CaptureMessage::CaptureError(capture_error) => {
+            error!(target: PAYLOAD_LOG_TARGET, "{}", capture_error);
+            window.emit("capture_notification", CaptureNotification {
+                message: capture_error,
+                received_at: Utc::now()
+            }).unwrap();
+        },
         CaptureMessage::Panic(panic_message) => {
-            panic!("{}", panic_message);
+            handle_payload_panic(window, panic_message);
+        },
+        CaptureMessage::ControlAck(ack) => {
+            control::acknowledge(ack);
+        },
+        CaptureMessage::Heartbeat(stats) => {
+            health::record_heartbeat(stats);
+        },
+        CaptureMessage::MessagesDropped(dropped) => {
+            health::record_drops(dropped);
         },
         _ => {
             MESSAGE_CONTAINER
@@ -170,12 +500,39 @@ fn handle_message(message: CaptureMessage) {
 
 }
 
+/*
+    A panic in the payload is a problem inside the game process, not ours, so rather than taking the app down
+    it's kept as an incident the user can choose to send with a crash report, and capture is stopped.
+*/
+fn handle_payload_panic(window: &tauri::Window, panic_message: String) {
+
+    error!(target: PAYLOAD_LOG_TARGET, "{}", panic_message);
+
+    // A replay or simulated capture isn't running inside the game, so the checksum would say nothing.
+    let game_checksum = match captured_pid() {
+        Some(_) => swtor_hook::get_checksum_hex().ok(),
+        None => None
+    };
+
+    match CaptureIncident::record(panic_message, game_checksum) {
+        Ok(incident) => {
+            window.emit("capture_incident", incident).unwrap();
+        },
+        Err(err) => {
+            error!("{}", err);
+        }
+    }
+
+    state::fail("Capture payload panicked".to_string());
+
+}
+
 fn start_logging_propagation(window: tauri::Window) {
 
     let messages = Arc::clone(&MESSAGE_CONTAINER);
     thread::spawn(move || {
 
-        while CONTINUE_LOGGING.load(Ordering::Relaxed) || !messages.lock().unwrap().unstored_messages.is_empty() {
+        while state::is_live() || messages.lock().unwrap().has_unstored() {
 
             let unstored_messages = messages
                 .lock()
@@ -187,6 +544,16 @@ fn start_logging_propagation(window: tauri::Window) {
                 window.emit("swtor_messages", unstored_messages).unwrap();
             }
 
+            let unstored_statuses = messages
+                .lock()
+                .unwrap()
+                .drain_unstored_statuses();
+
+            if !unstored_statuses.is_empty() {
+                FriendStatus::save_statuses_to_db(&unstored_statuses);
+                window.emit("friend_presence", unstored_statuses).unwrap();
+            }
+
             thread::sleep(Duration::from_secs(1));
 
         }
@@ -198,14 +565,153 @@ fn start_logging_propagation(window: tauri::Window) {
 #[tauri::command]
 pub fn stop_injecting_capture() {
 
-    if !INJECTED.load(Ordering::Relaxed) {
-        return;
+    if state::is_active() {
+
+        state::advance(CaptureState::Ejecting);
+
+        while state::is_active() {
+            thread::sleep(Duration::from_secs(1));
+        }
+
     }
 
-    CONTINUE_LOGGING.store(false, Ordering::Relaxed);
-    
-    while INJECTED.load(Ordering::Relaxed) {
-        thread::sleep(Duration::from_secs(1));
+    // Also clears a session left behind by a run that ended without stopping capture.
+    remove_capture_session();
+
+}
+
+#[tauri::command]
+pub fn get_capture_state() -> CaptureState {
+
+    state::get()
+
+}
+
+#[tauri::command]
+pub fn list_capture_recordings() -> Vec<String> {
+
+    recorder::list_recordings()
+
+}
+
+/*
+    Feeds a recording made by the recorder back through handle_message, as if the payload had sent it. The
+    original gaps between messages are kept, divided by `speed`. Stopping capture also stops a replay.
+*/
+#[tauri::command]
+pub fn replay_capture_recording(window: tauri::Window, path: String, speed: Option<f64>) -> Result<(), String> {
+
+    let speed = speed.unwrap_or(1.0);
+    if speed <= 0.0 {
+        return Err("Replay speed must be greater than zero".to_string());
+    }
+
+    if state::is_active() {
+        return Err("Capture is already running".to_string());
     }
 
-}
\ No newline at end of file
+    let recording = recorder::read_recording(&path)?;
+    info!("Replaying {} messages from {} at {}x", recording.len(), path, speed);
+
+    state::begin(window.clone(), CaptureState::Running)
+        .map_err(|_| "Capture is already running".to_string())?;
+    CAPTURED_PID.lock().unwrap().take();
+    start_logging_propagation(window.clone());
+
+    thread::spawn(move || {
+
+        let span = info_span!("capture_replay", path = %path);
+        let _entered = span.enter();
+
+        let mut previous = recording.first().map(|recorded| recorded.received_at);
+        for recorded in recording {
+
+            if !state::is_live() {
+                break;
+            }
+
+            if let Some(previous) = previous {
+
+                if let Ok(gap) = (recorded.received_at - previous).to_std() {
+                    thread::sleep(gap.div_f64(speed));
+                }
+
+            }
+            previous = Some(recorded.received_at);
+
+            match recorded.message {
+                CaptureMessage::Hello(_) => {},
+                // The panic already has its incident from when it was recorded, so this one is only logged.
+                CaptureMessage::Panic(panic_message) => {
+                    error!(target: PAYLOAD_LOG_TARGET, "{}", panic_message);
+                    state::fail("Replayed capture payload panicked".to_string());
+                },
+                message => handle_message(&window, message)
+            }
+
+        }
+
+        info!("Finished replaying {}", path);
+        state::advance(CaptureState::Ejecting);
+        state::finish();
+
+    });
+
+    Ok(())
+
+}
+
+#[tauri::command]
+pub fn get_capture_health() -> Option<CaptureHealth> {
+
+    health::get_health()
+
+}
+
+/// Waits for the payload's reply off the main thread, so the UI doesn't freeze for up to the reply timeout.
+async fn control_request(message: ControlMessage) -> Result<ControlReply, CaptureError> {
+
+    task::spawn_blocking(move || control::request(message))
+        .await
+        .unwrap_or(Err(CaptureError::ControlChannelClosed))
+
+}
+
+#[tauri::command]
+pub async fn pause_capture() -> Result<(), CaptureError> {
+
+    control_request(ControlMessage::Pause).await?;
+    Ok(())
+
+}
+
+#[tauri::command]
+pub async fn resume_capture() -> Result<(), CaptureError> {
+
+    control_request(ControlMessage::Resume).await?;
+    Ok(())
+
+}
+
+/// Round trip time to the payload in milliseconds.
+#[tauri::command]
+pub async fn ping_capture() -> Result<u64, CaptureError> {
+
+    let started = Instant::now();
+    let nonce   = Utc::now().timestamp_micros() as u64;
+
+    match control_request(ControlMessage::Ping(nonce)).await? {
+        ControlReply::Pong(pong) if pong == nonce => Ok(started.elapsed().as_millis() as u64),
+        reply => Err(CaptureError::ControlRejected(format!("Unexpected reply to ping: {:?}", reply)))
+    }
+
+}
+
+/// None captures every channel again.
+#[tauri::command]
+pub async fn set_capture_channel_filter(channels: Option<Vec<i32>>) -> Result<(), CaptureError> {
+
+    control_request(ControlMessage::SetChannelFilter(channels)).await?;
+    Ok(())
+
+}
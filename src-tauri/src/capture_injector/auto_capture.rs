@@ -0,0 +1,82 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use crate::dal::db::settings;
+use crate::swtor_hook;
+
+use super::state;
+use super::CaptureError;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long a new game process has to be around before we inject. Early on the client is still loading.
+const READY_DELAY: Duration = Duration::from_secs(10);
+
+/*
+    Opt-in with chat_log.auto_capture. Watches swtor_hook for a new game process, so capture survives the
+    game crashing or being restarted. A capture still attached to a process that has gone away is stopped
+    first, then the new process is injected once it has had time to get ready.
+
+    Each process is only tried once, unless it wasn't ready yet, so stopping capture by hand isn't undone on
+    the next poll.
+*/
+pub fn start_auto_capture_watcher(window: tauri::Window) {
+
+    thread::spawn(move || {
+
+        let mut seen: Option<(u32, Instant)> = None;
+        let mut attempted: Option<u32> = None;
+        loop {
+
+            thread::sleep(POLL_INTERVAL);
+            let pid = swtor_hook::get_pid();
+
+            if let Some(captured_pid) = super::captured_pid() {
+
+                if state::is_active() && pid != Some(captured_pid) {
+                    info!("SWTOR process {} is gone, stopping its capture", captured_pid);
+                    super::stop_injecting_capture();
+                } else if state::is_active() {
+                    attempted = Some(captured_pid);
+                }
+
+            }
+
+            seen = match (seen, pid) {
+                (Some((seen_pid, since)), Some(pid)) if seen_pid == pid => Some((seen_pid, since)),
+                (_, Some(pid)) => Some((pid, Instant::now())),
+                (_, None) => None
+            };
+
+            if !settings::get_settings().chat_log.auto_capture {
+                continue;
+            }
+
+            let (pid, since) = match seen {
+                Some(seen) => seen,
+                None => continue
+            };
+
+            if attempted == Some(pid) || since.elapsed() < READY_DELAY || state::is_active() {
+                continue;
+            }
+
+            match super::start_injecting_capture(window.clone()) {
+                Ok(_) => {
+                    info!("Automatically capturing SWTOR process {}", pid);
+                    attempted = Some(pid);
+                },
+                Err(CaptureError::NotYetFullyReady) => {},
+                Err(err) => {
+                    warn!("Unable to automatically capture SWTOR process {}: {:?}", pid, err);
+                    attempted = Some(pid);
+                }
+            }
+
+        }
+
+    });
+
+}
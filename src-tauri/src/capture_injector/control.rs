@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::error;
+
+use crate::share::control::{ControlAck, ControlMessage, ControlReply, ControlRequest};
+
+use super::CaptureError;
+
+struct ControlChannel {
+    stream: TcpStream,
+    secret: String
+}
+
+lazy_static! {
+    static ref CONTROL_CHANNEL: Arc<Mutex<Option<ControlChannel>>> = Arc::new(Mutex::new(None));
+    static ref REPLIES: Arc<Mutex<HashMap<u64, ControlReply>>> = Arc::new(Mutex::new(HashMap::new()));
+}
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+pub const DEFAULT_REPLY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Called once the payload has completed its handshake on `stream`.
+pub fn attach(stream: &TcpStream, secret: &str) {
+
+    match stream.try_clone() {
+        Ok(stream) => {
+            CONTROL_CHANNEL.lock().unwrap().replace(ControlChannel {
+                stream,
+                secret: secret.to_string()
+            });
+        },
+        Err(err) => {
+            error!("Error opening control channel: {:?}", err);
+        }
+    }
+
+}
+
+pub fn detach() {
+
+    CONTROL_CHANNEL.lock().unwrap().take();
+    REPLIES.lock().unwrap().clear();
+
+}
+
+pub fn send(message: ControlMessage) -> Result<u64, CaptureError> {
+
+    let mut channel = CONTROL_CHANNEL.lock().unwrap();
+    let channel = channel.as_mut().ok_or(CaptureError::NotInjected)?;
+
+    let request = ControlRequest {
+        id: NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed),
+        message
+    };
+
+    channel.stream
+        .write_all(&request.as_frame(&channel.secret))
+        .map_err(|err| {
+            error!("Error sending control request: {:?}", err);
+            CaptureError::ControlChannelClosed
+        })?;
+
+    Ok(request.id)
+
+}
+
+pub fn take_reply(id: u64) -> Option<ControlReply> {
+
+    REPLIES.lock().unwrap().remove(&id)
+
+}
+
+pub fn wait_for_reply(id: u64, timeout: Duration) -> Result<ControlReply, CaptureError> {
+
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+
+        if let Some(reply) = take_reply(id) {
+            return match reply {
+                ControlReply::Rejected(reason) => Err(CaptureError::ControlRejected(reason)),
+                reply => Ok(reply)
+            };
+        }
+        thread::sleep(Duration::from_millis(20));
+
+    }
+
+    Err(CaptureError::ControlTimeout)
+
+}
+
+/// Sends `message` and blocks until the payload acknowledges it.
+pub fn request(message: ControlMessage) -> Result<ControlReply, CaptureError> {
+
+    let id = send(message)?;
+    wait_for_reply(id, DEFAULT_REPLY_TIMEOUT)
+
+}
+
+pub fn acknowledge(ack: ControlAck) {
+
+    REPLIES.lock().unwrap().insert(ack.id, ack.reply);
+
+}
@@ -0,0 +1,174 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::dal::db::settings;
+use crate::share::health::{CaptureStats, DropReport};
+
+use super::state::{self, CaptureState};
+
+/// Messages the payload dropped before they reached us, so the chat log has a hole around `detected_at`.
+#[derive(Serialize, Clone)]
+pub struct CaptureGap {
+    pub detected_at: DateTime<Utc>,
+    pub dropped: DropReport
+}
+
+#[derive(Serialize, Clone)]
+pub struct CaptureHealth {
+    pub stalled: bool,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    pub stats: Option<CaptureStats>
+}
+
+struct HealthTracker {
+    last_seen: Instant,
+    last_heartbeat: Option<DateTime<Utc>>,
+    stats: Option<CaptureStats>,
+    stalled: bool,
+    changed: bool,
+    gaps: Vec<CaptureGap>
+}
+
+lazy_static! {
+    static ref HEALTH: Arc<Mutex<Option<HealthTracker>>> = Arc::new(Mutex::new(None));
+}
+
+/// Bumped by every monitor that starts, so one left over from a capture that was quickly restarted stops.
+static MONITOR_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Starts the stall timer. Called once the payload has completed its handshake.
+pub fn reset() {
+
+    HEALTH.lock().unwrap().replace(HealthTracker {
+        last_seen: Instant::now(),
+        last_heartbeat: None,
+        stats: None,
+        stalled: false,
+        changed: true,
+        gaps: Vec::new()
+    });
+
+}
+
+pub fn record_heartbeat(stats: CaptureStats) {
+
+    if let Some(tracker) = HEALTH.lock().unwrap().as_mut() {
+
+        if tracker.stalled {
+            state::advance(CaptureState::Running);
+        }
+
+        tracker.last_seen      = Instant::now();
+        tracker.last_heartbeat = Some(Utc::now());
+        tracker.stats          = Some(stats);
+        tracker.stalled        = false;
+        tracker.changed        = true;
+
+    }
+
+}
+
+pub fn record_drops(dropped: DropReport) {
+
+    warn!("Capture payload dropped {} global and {} other messages", dropped.global, dropped.other);
+
+    if let Some(tracker) = HEALTH.lock().unwrap().as_mut() {
+
+        tracker.gaps.push(CaptureGap {
+            detected_at: Utc::now(),
+            dropped
+        });
+
+    }
+
+}
+
+pub fn get_health() -> Option<CaptureHealth> {
+
+    HEALTH.lock().unwrap().as_ref().map(|tracker| tracker.as_health())
+
+}
+
+/*
+    Emits capture_health every time a heartbeat arrives, and once more when no heartbeat has been seen for
+    longer than the configured timeout. Drops reported by the payload are emitted as capture_gap.
+*/
+pub fn start_health_monitor(window: tauri::Window) {
+
+    let generation = MONITOR_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let is_current = move || MONITOR_GENERATION.load(Ordering::SeqCst) == generation;
+
+    thread::spawn(move || {
+
+        while state::is_live() && is_current() {
+
+            let timeout = Duration::from_secs(settings::get_settings().chat_log.capture_stall_timeout_secs);
+
+            let (health, gaps) = match HEALTH.lock().unwrap().as_mut() {
+                Some(tracker) => (tracker.poll(timeout), tracker.gaps.drain(..).collect()),
+                None => (None, Vec::new())
+            };
+
+            for gap in gaps {
+                window.emit("capture_gap", gap).unwrap();
+            }
+
+            if let Some(health) = health {
+
+                if health.stalled {
+                    warn!("No heartbeat from the capture payload in over {} seconds", timeout.as_secs());
+                    state::advance(CaptureState::Stalled);
+                }
+                window.emit("capture_health", health).unwrap();
+
+            }
+
+            thread::sleep(Duration::from_secs(1));
+
+        }
+
+        // A newer monitor owns the tracker by now.
+        let mut health = HEALTH.lock().unwrap();
+        if is_current() {
+            health.take();
+        }
+
+    });
+
+}
+
+impl HealthTracker {
+
+    fn poll(&mut self, timeout: Duration) -> Option<CaptureHealth> {
+
+        if !self.stalled && self.last_seen.elapsed() > timeout {
+            self.stalled = true;
+            self.changed = true;
+        }
+
+        if !self.changed {
+            return None;
+        }
+
+        self.changed = false;
+        Some(self.as_health())
+
+    }
+
+    fn as_health(&self) -> CaptureHealth {
+
+        CaptureHealth {
+            stalled: self.stalled,
+            last_heartbeat: self.last_heartbeat,
+            stats: self.stats.clone()
+        }
+
+    }
+
+}
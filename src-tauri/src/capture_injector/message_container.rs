@@ -1,14 +1,14 @@
 
 use crate::dal::db::settings;
 use crate::share::*;
-use crate::swtor::SwtorChannel;
 use crate::swtor_hook::post;
-use crate::utils::StringUtils;
 
 use crate::dal::db::swtor_message::SwtorMessage;
+use crate::dal::db::friend_presence::FriendStatus;
 
 pub struct SwtorMessageContainer {
-    pub unstored_messages: Vec<SwtorMessage>
+    pub unstored_messages: Vec<SwtorMessage>,
+    pub unstored_statuses: Vec<FriendStatus>
 }
 
 impl SwtorMessageContainer {
@@ -16,25 +16,24 @@ impl SwtorMessageContainer {
     pub fn new() -> SwtorMessageContainer {
 
         SwtorMessageContainer {
-            unstored_messages: Vec::new()
+            unstored_messages: Vec::new(),
+            unstored_statuses: Vec::new()
         }
 
     }
 
     pub fn push(&mut self, capture_message: CaptureMessage) {
 
+        if let CaptureMessage::FriendStatus { character, online } = capture_message {
+            self.unstored_statuses.push(FriendStatus::new(character, online));
+            return;
+        }
+
         if let CaptureMessage::Chat(raw_swtor_message) = capture_message {
 
             let swtor_message = SwtorMessage::from(raw_swtor_message);  
             if settings::get_settings().chat_log.retry_message_submission {
-                              
-                let channel = match SwtorChannel::try_from(swtor_message.channel) {
-                    Ok(channel) => channel,
-                    Err(_) => SwtorChannel::EMOTE
-                };
-
-                post::push_incoming_message_hash(channel, swtor_message.get_parsed_message().as_u64_hash());
-
+                post::push_incoming_message(&swtor_message);
             }
 
             self.unstored_messages.push(swtor_message);
@@ -43,6 +42,12 @@ impl SwtorMessageContainer {
 
     }
 
+    pub fn has_unstored(&self) -> bool {
+
+        !self.unstored_messages.is_empty() || !self.unstored_statuses.is_empty()
+
+    }
+
     pub fn drain_unstored(&mut self) -> Vec<SwtorMessage> {
 
         self.unstored_messages
@@ -51,4 +56,12 @@ impl SwtorMessageContainer {
 
     }
 
+    pub fn drain_unstored_statuses(&mut self) -> Vec<FriendStatus> {
+
+        self.unstored_statuses
+            .drain(..)
+            .collect()
+
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,119 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Local, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{error, info};
+
+use crate::dal;
+use crate::share::CaptureMessage;
+
+/*
+    Tees every decoded CaptureMessage into a JSONL file, one {"received_at", "message"} object per line, so
+    a session can later be replayed through the same pipeline without the game running.
+*/
+
+lazy_static! {
+    static ref RECORDING: Arc<Mutex<Option<BufWriter<File>>>> = Arc::new(Mutex::new(None));
+}
+
+#[derive(Deserialize)]
+pub struct RecordedMessage {
+    pub received_at: DateTime<Utc>,
+    pub message: CaptureMessage
+}
+
+pub fn recordings_dir() -> PathBuf {
+
+    PathBuf::from(dal::get_em_dirs().get_data_dir_path("recordings"))
+
+}
+
+pub fn start() {
+
+    let dir = recordings_dir();
+    if let Err(err) = fs::create_dir_all(&dir) {
+        error!("Error creating recordings directory: {:?}", err);
+        return;
+    }
+
+    let path = dir.join(format!("capture-{}.jsonl", Local::now().format("%Y%m%d-%H%M%S")));
+    match File::create(&path) {
+        Ok(file) => {
+            info!("Recording capture stream to {:?}", path);
+            RECORDING.lock().unwrap().replace(BufWriter::new(file));
+        },
+        Err(err) => {
+            error!("Error creating recording {:?}: {:?}", path, err);
+        }
+    }
+
+}
+
+pub fn record(message: &CaptureMessage) {
+
+    if let Some(writer) = RECORDING.lock().unwrap().as_mut() {
+
+        let line = json!({
+            "received_at": Utc::now(),
+            "message": message
+        });
+
+        if let Err(err) = writeln!(writer, "{}", line) {
+            error!("Error writing to recording: {:?}", err);
+        }
+
+    }
+
+}
+
+pub fn stop() {
+
+    if let Some(mut writer) = RECORDING.lock().unwrap().take() {
+        let _ = writer.flush();
+    }
+
+}
+
+pub fn read_recording(path: &str) -> Result<Vec<RecordedMessage>, String> {
+
+    let file = File::open(path)
+        .map_err(|e| format!("Error opening recording: {}", e))?;
+
+    let mut messages = Vec::new();
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+
+        let line = line.map_err(|e| format!("Error reading recording: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message: RecordedMessage = serde_json::from_str(&line)
+            .map_err(|e| format!("Line {}: {}", idx + 1, e))?;
+        messages.push(message);
+
+    }
+
+    Ok(messages)
+
+}
+
+pub fn list_recordings() -> Vec<String> {
+
+    let mut recordings: Vec<String> = match fs::read_dir(recordings_dir()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "jsonl"))
+            .filter_map(|path| path.to_str().map(|path| path.to_string()))
+            .collect(),
+        Err(_) => Vec::new()
+    };
+
+    recordings.sort();
+    recordings
+
+}
@@ -0,0 +1,157 @@
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::CaptureError;
+
+/*
+    Where capture is in its lifecycle. Every change is emitted as capture_state.
+
+    Idle -> Injecting -> Handshaking -> Running <-> Stalled -> Ejecting -> Idle
+
+    Any live state can move to Ejecting, and a capture that was stopped because something went wrong ends in
+    Failed instead of Idle. Failed behaves like Idle otherwise; it only remembers why.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum CaptureState {
+    Idle,
+    Injecting,
+    Handshaking,
+    Running,
+    Stalled,
+    Ejecting,
+    Failed(String)
+}
+
+struct StateTracker {
+    state: CaptureState,
+    failure: Option<String>,
+    window: Option<tauri::Window>
+}
+
+lazy_static! {
+    static ref STATE: Arc<Mutex<StateTracker>> = Arc::new(Mutex::new(StateTracker {
+        state: CaptureState::Idle,
+        failure: None,
+        window: None
+    }));
+}
+
+impl CaptureState {
+
+    /// Capture is running, or on its way there. Threads keep working while this is true.
+    pub fn is_live(&self) -> bool {
+
+        matches!(self, CaptureState::Injecting | CaptureState::Handshaking | CaptureState::Running | CaptureState::Stalled)
+
+    }
+
+    /// Something is still attached or winding down, so a new capture can't start yet.
+    pub fn is_active(&self) -> bool {
+
+        !matches!(self, CaptureState::Idle | CaptureState::Failed(_))
+
+    }
+
+}
+
+pub fn get() -> CaptureState {
+
+    STATE.lock().unwrap().state.clone()
+
+}
+
+pub fn is_live() -> bool {
+
+    get().is_live()
+
+}
+
+pub fn is_active() -> bool {
+
+    get().is_active()
+
+}
+
+/// Claims capture for a new session, starting in `initial`.
+pub fn begin(window: tauri::Window, initial: CaptureState) -> Result<(), CaptureError> {
+
+    let mut tracker = STATE.lock().unwrap();
+    if tracker.state.is_active() {
+        return Err(CaptureError::AlreadyInjected);
+    }
+
+    tracker.failure = None;
+    tracker.window  = Some(window);
+    tracker.set(initial);
+    Ok(())
+
+}
+
+/// Moves a live capture to `state`. Ignored once capture is stopping, so a late handshake can't revive it.
+pub fn advance(state: CaptureState) {
+
+    let mut tracker = STATE.lock().unwrap();
+    if tracker.state.is_live() {
+        tracker.set(state);
+    }
+
+}
+
+/// Like advance, but only from `from`. Used where the listener thread may already have moved past it.
+pub fn advance_from(from: CaptureState, state: CaptureState) {
+
+    let mut tracker = STATE.lock().unwrap();
+    if tracker.state == from {
+        tracker.set(state);
+    }
+
+}
+
+/// Stops capture and remembers why, so it ends in Failed rather than Idle.
+pub fn fail(reason: String) {
+
+    let mut tracker = STATE.lock().unwrap();
+    if tracker.failure.is_none() {
+        tracker.failure = Some(reason);
+    }
+
+    if tracker.state.is_live() {
+        tracker.set(CaptureState::Ejecting);
+    }
+
+}
+
+/// Called once everything the session started has wound down.
+pub fn finish() {
+
+    let mut tracker = STATE.lock().unwrap();
+    let state = match tracker.failure.take() {
+        Some(reason) => CaptureState::Failed(reason),
+        None => CaptureState::Idle
+    };
+
+    tracker.set(state);
+    tracker.window.take();
+
+}
+
+impl StateTracker {
+
+    fn set(&mut self, state: CaptureState) {
+
+        if self.state == state {
+            return;
+        }
+
+        info!("Capture state {:?} -> {:?}", self.state, state);
+        self.state = state;
+
+        if let Some(window) = self.window.as_ref() {
+            window.emit("capture_state", self.state.clone()).unwrap();
+        }
+
+    }
+
+}
@@ -0,0 +1,419 @@
+/*
+    Stands in for the injected swtor_chat_capture DLL. Start capture from the app with start_simulated_capture,
+    then run this to connect to it and stream synthetic chat traffic.
+
+    cargo run --bin capture_simulator -- --emotes 20 --whispers 5 --global 120 --not-found 1 --afk 1 --presence 2
+
+    Rates are messages per minute. Lines typed on stdin are sent as if --player had said them in /emote, which
+    is enough to confirm posts while exercising the retry logic in swtor_hook::post.
+*/
+
+use std::io::prelude::*;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/*
+    Sits next to main.rs and lib.rs rather than in src/bin, so share's submodules resolve to src/share the same
+    way they do for the app and the DLL.
+*/
+#[allow(dead_code)]
+mod share;
+
+#[allow(dead_code)]
+mod swtor;
+
+use share::CaptureMessage;
+use share::codec::FrameDecoder;
+use share::control::{ControlAck, ControlMessage, ControlReply, ControlRequest};
+use share::handshake::{CaptureHello, HookOffsets};
+use share::health::{CaptureStats, HEARTBEAT_INTERVAL};
+use share::raw_swtor_message::RawSwtorMessage;
+use share::session::CaptureSession;
+use swtor::SwtorChannel;
+
+const CHARACTERS: [&str; 8] = ["Kaelyn", "Torven", "Ashara'vel", "Dremmick", "Syla Vorn", "Jex", "Mirrah", "Oskarr"];
+
+const EMOTES: [&str; 6] = [
+    "leans against the cantina bar, eyes drifting to the door whenever it hisses open.",
+    "sets the datapad down with a sigh, tapping a gloved finger against the casing.",
+    "glances over her shoulder before lowering her voice. \"Not here. Too many ears.\"",
+    "chuckles, shaking his head as he slides a credit chip across the table.",
+    "straightens at the sound of approaching boots, one hand resting on the hilt at her hip.",
+    "offers a lopsided grin. \"You're late. Again.\""
+];
+
+const WHISPERS: [&str; 4] = [
+    "Are you still up for the scene tonight?",
+    "OOC: brb, dinner",
+    "Meet at the Nar Shaddaa cantina in 10?",
+    "That last post was great, loved the twist"
+];
+
+const GLOBAL: [&str; 5] = [
+    "LF2M HM Dxun need tank and heals",
+    "WTS Dark Honor Guard dye, pst",
+    "Guild <Lost Horizon> recruiting RPers on Star Forge, whisper for info",
+    "Anyone know where the datacron on Ossus is?",
+    "LFG GSF, any role"
+];
+
+struct Options {
+    player: String,
+    emotes: f64,
+    whispers: f64,
+    global: f64,
+    not_found: f64,
+    afk: f64,
+    presence: f64,
+    duration: Option<Duration>
+}
+
+struct Simulator {
+    stream: TcpStream,
+    secret: String,
+    stats: CaptureStats,
+    paused: bool,
+    channel_filter: Option<Vec<i32>>
+}
+
+/// Xorshift is plenty for picking lines and rolling rates.
+struct Rng(u64);
+
+impl Rng {
+
+    fn new() -> Rng {
+
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x2545F4914F6CDD1D);
+
+        Rng(seed | 1)
+
+    }
+
+    fn next(&mut self) -> u64 {
+
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+
+        (self.next() % 1_000_000) as f64 / 1_000_000.0 < probability
+
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+
+        items[(self.next() % items.len() as u64) as usize]
+
+    }
+
+}
+
+fn main() {
+
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("Usage: capture_simulator [--player NAME] [--emotes N] [--whispers N] [--global N] [--not-found N] [--afk N] [--presence N] [--duration SECS]");
+            std::process::exit(1);
+        }
+    };
+
+    let session = match CaptureSession::read() {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("{}. Start a simulated capture from ChaTOR first.", err);
+            std::process::exit(1);
+        }
+    };
+
+    let stream = match TcpStream::connect(("127.0.0.1", session.capture_port)) {
+        Ok(stream) => stream,
+        Err(err) => {
+            eprintln!("Error connecting to ChaTOR on port {}: {}", session.capture_port, err);
+            std::process::exit(1);
+        }
+    };
+
+    let simulator = Arc::new(Mutex::new(Simulator {
+        stream,
+        secret: session.secret,
+        stats: CaptureStats::default(),
+        paused: false,
+        channel_filter: None
+    }));
+
+    let quit = Arc::new(AtomicBool::new(false));
+
+    simulator.lock().unwrap().send(CaptureMessage::Hello(CaptureHello::new(HookOffsets {
+        chat: None,
+        friends_list: None
+    })));
+    println!("Connected to ChaTOR on port {}", session.capture_port);
+
+    start_control_reader(Arc::clone(&simulator), Arc::clone(&quit));
+    start_stdin_echo(Arc::clone(&simulator), options.player.clone());
+    run_traffic(simulator, quit, options);
+
+}
+
+fn parse_options() -> Result<Options, String> {
+
+    let mut options = Options {
+        player: "Testplayer".to_string(),
+        emotes: 20.0,
+        whispers: 4.0,
+        global: 60.0,
+        not_found: 0.0,
+        afk: 0.0,
+        presence: 0.0,
+        duration: None
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+
+        let value = args.next().ok_or(format!("Missing value for {}", flag))?;
+        let rate  = || value.parse::<f64>().map_err(|_| format!("Invalid value for {}: {}", flag, value));
+
+        match flag.as_str() {
+            "--player"    => options.player    = value.clone(),
+            "--emotes"    => options.emotes    = rate()?,
+            "--whispers"  => options.whispers  = rate()?,
+            "--global"    => options.global    = rate()?,
+            "--not-found" => options.not_found = rate()?,
+            "--afk"       => options.afk       = rate()?,
+            "--presence"  => options.presence  = rate()?,
+            "--duration"  => options.duration  = Some(Duration::from_secs_f64(rate()?)),
+            _ => return Err(format!("Unknown option {}", flag))
+        }
+
+    }
+
+    Ok(options)
+
+}
+
+impl Simulator {
+
+    fn send(&mut self, message: CaptureMessage) {
+
+        if let Err(err) = self.stream.write_all(&message.as_frame(&self.secret)) {
+            eprintln!("Error writing to ChaTOR: {}", err);
+            std::process::exit(1);
+        }
+
+    }
+
+    fn send_chat(&mut self, channel: SwtorChannel, from: &str, to: &str, message: &str) {
+
+        let channel = channel as i32;
+        if self.paused {
+            return;
+        }
+
+        if let Some(channels) = self.channel_filter.as_ref() {
+            if !channels.contains(&channel) {
+                return;
+            }
+        }
+
+        self.stats.messages_captured += 1;
+        self.send(CaptureMessage::Chat(RawSwtorMessage::new(channel, from.to_string(), to.to_string(), message.to_string())));
+
+    }
+
+    fn send_friend_status(&mut self, character: &str, online: bool) {
+
+        if self.paused {
+            return;
+        }
+
+        self.send(CaptureMessage::FriendStatus {
+            character: character.to_string(),
+            online
+        });
+
+    }
+
+    fn handle_control_request(&mut self, request: ControlRequest) -> bool {
+
+        let quit = matches!(request.message, ControlMessage::Quit);
+        let reply = match request.message {
+            ControlMessage::Quit => ControlReply::Ack,
+            ControlMessage::Pause => {
+                self.paused = true;
+                ControlReply::Ack
+            },
+            ControlMessage::Resume => {
+                self.paused = false;
+                ControlReply::Ack
+            },
+            ControlMessage::Ping(nonce) => ControlReply::Pong(nonce),
+            ControlMessage::SetChannelFilter(channels) => {
+                self.channel_filter = channels;
+                ControlReply::Ack
+            }
+        };
+
+        self.send(CaptureMessage::ControlAck(ControlAck {
+            id: request.id,
+            reply
+        }));
+        quit
+
+    }
+
+}
+
+fn start_control_reader(simulator: Arc<Mutex<Simulator>>, quit: Arc<AtomicBool>) {
+
+    let (mut stream, secret) = {
+        let simulator = simulator.lock().unwrap();
+        (simulator.stream.try_clone().unwrap(), simulator.secret.clone())
+    };
+
+    thread::spawn(move || {
+
+        let mut decoder = FrameDecoder::new();
+        let mut buffer: [u8; 512] = [0; 512];
+        loop {
+
+            match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => decoder.push(&buffer[..read])
+            }
+
+            let mut out_of_sync = false;
+            while let Some(frame) = decoder.next_frame() {
+
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        eprintln!("Control channel out of sync: {:?}", err);
+                        out_of_sync = true;
+                        break;
+                    }
+                };
+
+                match ControlRequest::from_frame(&secret, &frame) {
+                    Ok(request) => {
+                        println!("Control request: {:?}", request.message);
+                        if simulator.lock().unwrap().handle_control_request(request) {
+                            quit.store(true, Ordering::Relaxed);
+                        }
+                    },
+                    Err(_) => {
+                        eprintln!("Received an unauthenticated control request");
+                    }
+                }
+
+            }
+
+            if out_of_sync {
+                break;
+            }
+
+        }
+
+        quit.store(true, Ordering::Relaxed);
+
+    });
+
+}
+
+fn start_stdin_echo(simulator: Arc<Mutex<Simulator>>, player: String) {
+
+    thread::spawn(move || {
+
+        for line in std::io::stdin().lock().lines() {
+
+            match line {
+                Ok(line) if !line.trim().is_empty() => {
+                    simulator.lock().unwrap().send_chat(SwtorChannel::EMOTE, &player, "", line.trim());
+                },
+                Ok(_) => {},
+                Err(_) => break
+            }
+
+        }
+
+    });
+
+}
+
+fn run_traffic(simulator: Arc<Mutex<Simulator>>, quit: Arc<AtomicBool>, options: Options) {
+
+    const TICK: Duration = Duration::from_millis(100);
+    let per_tick = |per_minute: f64| per_minute * TICK.as_secs_f64() / 60.0;
+
+    let mut rng = Rng::new();
+    let started = Instant::now();
+    let mut last_heartbeat = Instant::now();
+
+    while !quit.load(Ordering::Relaxed) {
+
+        if let Some(duration) = options.duration {
+            if started.elapsed() > duration {
+                break;
+            }
+        }
+
+        let mut simulator = simulator.lock().unwrap();
+
+        if rng.chance(per_tick(options.emotes)) {
+            let from = rng.pick(&CHARACTERS);
+            simulator.send_chat(SwtorChannel::EMOTE, from, "", rng.pick(&EMOTES));
+        }
+
+        if rng.chance(per_tick(options.whispers)) {
+            let from = rng.pick(&CHARACTERS);
+            simulator.send_chat(SwtorChannel::WHISPER, from, &options.player, rng.pick(&WHISPERS));
+        }
+
+        if rng.chance(per_tick(options.global)) {
+            let from = rng.pick(&CHARACTERS);
+            simulator.send_chat(SwtorChannel::GLOBAL, from, "", rng.pick(&GLOBAL));
+        }
+
+        if rng.chance(per_tick(options.not_found)) {
+            let name = rng.pick(&CHARACTERS);
+            simulator.send_chat(SwtorChannel::PlayerNotFound, "", "", &format!("{} is not online.", name));
+        }
+
+        if rng.chance(per_tick(options.afk)) {
+            let from = rng.pick(&CHARACTERS);
+            simulator.send_chat(SwtorChannel::PlayerAFK, from, &options.player, "I am away from the keyboard.");
+        }
+
+        if rng.chance(per_tick(options.presence)) {
+            let character = rng.pick(&CHARACTERS);
+            let online = rng.chance(0.5);
+            simulator.send_friend_status(character, online);
+        }
+
+        if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            let stats = simulator.stats.clone();
+            simulator.send(CaptureMessage::Heartbeat(stats));
+            last_heartbeat = Instant::now();
+        }
+
+        drop(simulator);
+        thread::sleep(TICK);
+
+    }
+
+    println!("Simulator finished after {:.0} seconds", started.elapsed().as_secs_f64());
+
+}
@@ -7,11 +7,14 @@ mod sys_info;
 
 use sys_info::SysInfo;
 use crate::config::config;
+use crate::dal::db::capture_incident::CaptureIncident;
 
 #[derive(Serialize, Deserialize)]
 pub struct CrashReporter {
     pub sys_info: SysInfo,
-    pub crash_report: String
+    pub crash_report: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_incident: Option<CaptureIncident>
 }
 
 impl CrashReporter {
@@ -20,11 +23,19 @@ impl CrashReporter {
 
         Self {
             sys_info: SysInfo::default(),
-            crash_report: crash_report
+            crash_report: crash_report,
+            capture_incident: None
         }
 
     }
 
+    pub fn with_capture_incident(mut self, capture_incident: CaptureIncident) -> Self {
+
+        self.capture_incident = Some(capture_incident);
+        self
+
+    }
+
     pub fn submit(&self) {
 
         let client = Client::new();
@@ -45,4 +56,17 @@ impl CrashReporter {
 
     }
 
-}
\ No newline at end of file
+}
+
+/// Only ever sent when the user asks, from the incident the UI was told about.
+#[tauri::command]
+pub fn submit_capture_incident_report(capture_incident_id: i32) -> Result<(), &'static str> {
+
+    let capture_incident = CaptureIncident::get(capture_incident_id)?;
+    CrashReporter::new("Capture payload panicked".to_string())
+        .with_capture_incident(capture_incident)
+        .submit();
+
+    Ok(())
+
+}
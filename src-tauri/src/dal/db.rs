@@ -12,6 +12,11 @@ pub mod user_character_messages;
 pub mod swtor_message;
 pub mod migration;
 pub mod custom_channel;
+pub mod friend_presence;
+pub mod capture_incident;
+pub mod scheduled_post;
+pub mod recipient_group;
+pub mod post_macro;
 
 use migration::{Migration, run_non_sql_migrations};
 use custom_emote::CustomEmote;
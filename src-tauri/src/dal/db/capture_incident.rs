@@ -0,0 +1,104 @@
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::dal::db;
+
+/// A panic inside the injected capture payload. Kept so it can be sent along with a crash report later.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CaptureIncident {
+    pub capture_incident_id: i32,
+    pub message: String,
+    pub game_checksum: Option<String>,
+    pub timestamp: String
+}
+
+impl CaptureIncident {
+
+    pub fn record(message: String, game_checksum: Option<String>) -> Result<CaptureIncident, &'static str> {
+
+        let conn = db::get_connection();
+        const INSERT_QUERY: &str =
+        "
+            INSERT INTO CaptureIncidents (message, game_checksum)
+            VALUES (?1, ?2)
+            RETURNING
+                capture_incident_id,
+                message,
+                game_checksum,
+                datetime(timestamp, 'localtime');
+        ";
+
+        conn.query_row(INSERT_QUERY, params![&message, &game_checksum], CaptureIncident::from_row)
+            .map_err(|_| "Error recording capture incident")
+
+    }
+
+    pub fn get(capture_incident_id: i32) -> Result<CaptureIncident, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                capture_incident_id,
+                message,
+                game_checksum,
+                datetime(timestamp, 'localtime')
+            FROM
+                CaptureIncidents
+            WHERE
+                capture_incident_id = ?1;
+        ";
+
+        conn.query_row(SELECT_QUERY, params![capture_incident_id], CaptureIncident::from_row)
+            .map_err(|_| "Capture incident not found")
+
+    }
+
+    pub fn get_all() -> Result<Vec<CaptureIncident>, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                capture_incident_id,
+                message,
+                game_checksum,
+                datetime(timestamp, 'localtime')
+            FROM
+                CaptureIncidents
+            ORDER BY capture_incident_id DESC;
+        ";
+
+        let mut stmt = match conn.prepare(SELECT_QUERY) {
+            Ok(stmt) => stmt,
+            Err(_) => return Err("Error getting capture incidents")
+        };
+
+        match stmt.query_map(params![], CaptureIncident::from_row) {
+            Ok(incidents) => incidents
+                .collect::<Result<Vec<CaptureIncident>, rusqlite::Error>>()
+                .map_err(|_| "Error reading capture incidents"),
+            Err(_) => Err("Error getting capture incidents")
+        }
+
+    }
+
+    fn from_row(row: &Row<'_>) -> Result<CaptureIncident, rusqlite::Error> {
+
+        Ok(CaptureIncident {
+            capture_incident_id: row.get(0)?,
+            message: row.get(1)?,
+            game_checksum: row.get(2)?,
+            timestamp: row.get(3)?
+        })
+
+    }
+
+}
+
+#[tauri::command]
+pub fn get_capture_incidents() -> Result<Vec<CaptureIncident>, &'static str> {
+
+    CaptureIncident::get_all()
+
+}
@@ -0,0 +1,139 @@
+use chrono::prelude::*;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::dal::db;
+
+/// A single login or logout reported by the friends list detour.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FriendStatus {
+    pub character_name: String,
+    pub online: bool,
+    pub timestamp: DateTime<Utc>
+}
+
+/// The latest known status of a character. `last_seen` is when that status last changed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FriendPresence {
+    pub character_id: i32,
+    pub character_name: String,
+    pub online: bool,
+    pub last_seen: String
+}
+
+impl FriendStatus {
+
+    pub fn new(character_name: String, online: bool) -> FriendStatus {
+
+        FriendStatus {
+            character_name,
+            online,
+            timestamp: Utc::now()
+        }
+
+    }
+
+    pub fn save_statuses_to_db(statuses: &[FriendStatus]) {
+
+        let conn = db::get_connection();
+
+        const INSERT_PLAYER: &str =
+        "
+            INSERT OR IGNORE INTO
+                Characters (character_name)
+            SELECT
+                ?1
+            WHERE NOT EXISTS (SELECT 1 FROM Characters WHERE character_name = ?1);
+        ";
+
+        const INSERT_STATUS: &str =
+        "
+            INSERT INTO
+                FriendPresence (character_id, online, timestamp)
+            SELECT
+                C.character_id,
+                ?2,
+                ?3
+            FROM
+                Characters C
+            WHERE
+                C.character_name = ?1;
+        ";
+
+        let mut insert_player = conn.prepare(INSERT_PLAYER).unwrap();
+        let mut insert_status = conn.prepare(INSERT_STATUS).unwrap();
+        for status in statuses.iter() {
+
+            let timestamp = status.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+            let result = insert_player
+                .execute(params![&status.character_name])
+                .and_then(|_| insert_status.execute(params![&status.character_name, status.online, timestamp]));
+
+            if let Err(err) = result {
+                error!("Error saving presence for {}: {}", status.character_name, err);
+            }
+
+        }
+
+    }
+
+}
+
+impl FriendPresence {
+
+    pub fn get_all() -> Result<Vec<FriendPresence>, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                C.character_id,
+                C.character_name,
+                FP.online,
+                datetime(FP.timestamp, 'localtime')
+            FROM
+                FriendPresence FP
+                INNER JOIN Characters C ON C.character_id = FP.character_id
+            WHERE
+                FP.friend_presence_id = (
+                    SELECT MAX(friend_presence_id) FROM FriendPresence WHERE character_id = FP.character_id
+                )
+            ORDER BY
+                FP.online DESC,
+                FP.timestamp DESC;
+        ";
+
+        let mut stmt = match conn.prepare(SELECT_QUERY) {
+            Ok(stmt) => stmt,
+            Err(_) => return Err("Error getting friend presence")
+        };
+
+        let presence = stmt.query_map(params![], |row| {
+
+            Ok(FriendPresence {
+                character_id: row.get(0)?,
+                character_name: row.get(1)?,
+                online: row.get(2)?,
+                last_seen: row.get(3)?
+            })
+
+        });
+
+        match presence {
+            Ok(presence) => presence
+                .collect::<Result<Vec<FriendPresence>, rusqlite::Error>>()
+                .map_err(|_| "Error reading friend presence"),
+            Err(_) => Err("Error getting friend presence")
+        }
+
+    }
+
+}
+
+#[tauri::command]
+pub fn get_friends_presence() -> Result<Vec<FriendPresence>, &'static str> {
+
+    FriendPresence::get_all()
+
+}
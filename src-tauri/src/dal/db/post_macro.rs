@@ -0,0 +1,158 @@
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::dal::db;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PostMacro {
+    pub post_macro_id: Option<i32>,
+    pub macro_name: String,
+    pub source: String
+}
+
+impl PostMacro {
+
+    pub fn get(post_macro_id: i32) -> Result<PostMacro, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                post_macro_id,
+                macro_name,
+                source
+            FROM
+                PostMacros
+            WHERE
+                post_macro_id = ?1;
+        ";
+
+        conn.query_row(SELECT_QUERY, params![post_macro_id], PostMacro::from_row)
+            .map_err(|_| "Macro not found")
+
+    }
+
+    pub fn get_all() -> Result<Vec<PostMacro>, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                post_macro_id,
+                macro_name,
+                source
+            FROM
+                PostMacros
+            ORDER BY
+                macro_name ASC;
+        ";
+
+        let mut stmt = match conn.prepare(SELECT_QUERY) {
+            Ok(stmt) => stmt,
+            Err(_) => return Err("Error getting macros")
+        };
+
+        match stmt.query_map(params![], PostMacro::from_row) {
+            Ok(post_macros) => post_macros
+                .collect::<Result<Vec<PostMacro>, rusqlite::Error>>()
+                .map_err(|_| "Error reading macros"),
+            Err(_) => Err("Error getting macros")
+        }
+
+    }
+
+    pub fn save(mut self) -> Result<PostMacro, &'static str> {
+
+        if self.macro_name.trim().is_empty() {
+            return Err("Macro name cannot be empty");
+        }
+
+        let conn = db::get_connection();
+        if let Some(post_macro_id) = self.post_macro_id {
+
+            const UPDATE_QUERY: &str =
+            "
+                UPDATE
+                    PostMacros
+                SET
+                    macro_name = ?1,
+                    source = ?2
+                WHERE
+                    post_macro_id = ?3;
+            ";
+
+            let updated = conn.execute(UPDATE_QUERY, params![&self.macro_name, &self.source, post_macro_id])
+                .map_err(|_| "Error saving macro")?;
+
+            if updated == 0 {
+                return Err("Macro not found");
+            }
+            return Ok(self);
+
+        }
+
+        const INSERT_QUERY: &str =
+        "
+            INSERT INTO PostMacros (macro_name, source)
+            VALUES (?1, ?2)
+            RETURNING post_macro_id;
+        ";
+
+        let post_macro_id: i32 = conn
+            .query_row(INSERT_QUERY, params![&self.macro_name, &self.source], |row| row.get(0))
+            .map_err(|_| "Error inserting macro")?;
+
+        self.post_macro_id = Some(post_macro_id);
+        Ok(self)
+
+    }
+
+    pub fn delete(post_macro_id: i32) -> Result<(), &'static str> {
+
+        let conn = db::get_connection();
+        const DELETE_QUERY: &str =
+        "
+            DELETE FROM
+                PostMacros
+            WHERE
+                post_macro_id = ?1;
+        ";
+
+        conn.execute(DELETE_QUERY, params![post_macro_id])
+            .map(|_| ())
+            .map_err(|_| "Error deleting macro")
+
+    }
+
+    fn from_row(row: &Row<'_>) -> Result<PostMacro, rusqlite::Error> {
+
+        Ok(PostMacro {
+            post_macro_id: row.get(0)?,
+            macro_name: row.get(1)?,
+            source: row.get(2)?
+        })
+
+    }
+
+}
+
+#[tauri::command]
+pub fn get_all_post_macros() -> Result<Vec<PostMacro>, &'static str> {
+
+    PostMacro::get_all()
+
+}
+
+#[tauri::command]
+pub fn save_post_macro(post_macro: PostMacro) -> Result<PostMacro, &'static str> {
+
+    post_macro.save()
+
+}
+
+#[tauri::command]
+pub fn delete_post_macro(post_macro_id: i32) -> Result<(), &'static str> {
+
+    PostMacro::delete(post_macro_id)
+
+}
@@ -0,0 +1,180 @@
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::dal::db;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecipientGroup {
+    pub recipient_group_id: Option<i32>,
+    pub group_name: String,
+    pub recipients: Vec<String>
+}
+
+impl RecipientGroup {
+
+    pub fn get(recipient_group_id: i32) -> Result<RecipientGroup, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                recipient_group_id,
+                group_name,
+                recipients
+            FROM
+                RecipientGroups
+            WHERE
+                recipient_group_id = ?1;
+        ";
+
+        conn.query_row(SELECT_QUERY, params![recipient_group_id], RecipientGroup::from_row)
+            .map_err(|_| "Recipient group not found")
+
+    }
+
+    pub fn get_all() -> Result<Vec<RecipientGroup>, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                recipient_group_id,
+                group_name,
+                recipients
+            FROM
+                RecipientGroups
+            ORDER BY
+                group_name ASC;
+        ";
+
+        let mut stmt = match conn.prepare(SELECT_QUERY) {
+            Ok(stmt) => stmt,
+            Err(_) => return Err("Error getting recipient groups")
+        };
+
+        match stmt.query_map(params![], RecipientGroup::from_row) {
+            Ok(recipient_groups) => recipient_groups
+                .collect::<Result<Vec<RecipientGroup>, rusqlite::Error>>()
+                .map_err(|_| "Error reading recipient groups"),
+            Err(_) => Err("Error getting recipient groups")
+        }
+
+    }
+
+    pub fn save(mut self) -> Result<RecipientGroup, &'static str> {
+
+        if self.group_name.trim().is_empty() {
+            return Err("Recipient group name cannot be empty");
+        }
+
+        self.recipients = self.recipients
+            .iter()
+            .map(|recipient| recipient.trim().to_string())
+            .filter(|recipient| !recipient.is_empty())
+            .collect();
+
+        if self.recipients.is_empty() {
+            return Err("Recipient group has no recipients");
+        }
+
+        // Broadcasting whispers "/w name: message", so a name with ':' in it could never be sent to.
+        if self.recipients.iter().any(|recipient| recipient.contains(":")) {
+            return Err("Recipient names cannot contain ':'");
+        }
+
+        let recipients = serde_json::to_string(&self.recipients)
+            .map_err(|_| "Error serializing recipients")?;
+
+        let conn = db::get_connection();
+        if let Some(recipient_group_id) = self.recipient_group_id {
+
+            const UPDATE_QUERY: &str =
+            "
+                UPDATE
+                    RecipientGroups
+                SET
+                    group_name = ?1,
+                    recipients = ?2
+                WHERE
+                    recipient_group_id = ?3;
+            ";
+
+            let updated = conn.execute(UPDATE_QUERY, params![&self.group_name, recipients, recipient_group_id])
+                .map_err(|_| "Error saving recipient group")?;
+
+            if updated == 0 {
+                return Err("Recipient group not found");
+            }
+            return Ok(self);
+
+        }
+
+        const INSERT_QUERY: &str =
+        "
+            INSERT INTO RecipientGroups (group_name, recipients)
+            VALUES (?1, ?2)
+            RETURNING recipient_group_id;
+        ";
+
+        let recipient_group_id: i32 = conn
+            .query_row(INSERT_QUERY, params![&self.group_name, recipients], |row| row.get(0))
+            .map_err(|_| "Error inserting recipient group")?;
+
+        self.recipient_group_id = Some(recipient_group_id);
+        Ok(self)
+
+    }
+
+    pub fn delete(recipient_group_id: i32) -> Result<(), &'static str> {
+
+        let conn = db::get_connection();
+        const DELETE_QUERY: &str =
+        "
+            DELETE FROM
+                RecipientGroups
+            WHERE
+                recipient_group_id = ?1;
+        ";
+
+        conn.execute(DELETE_QUERY, params![recipient_group_id])
+            .map(|_| ())
+            .map_err(|_| "Error deleting recipient group")
+
+    }
+
+    fn from_row(row: &Row<'_>) -> Result<RecipientGroup, rusqlite::Error> {
+
+        let recipients: String = row.get(2)?;
+        let recipients: Vec<String> = serde_json::from_str(&recipients)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e)))?;
+
+        Ok(RecipientGroup {
+            recipient_group_id: row.get(0)?,
+            group_name: row.get(1)?,
+            recipients
+        })
+
+    }
+
+}
+
+#[tauri::command]
+pub fn get_all_recipient_groups() -> Result<Vec<RecipientGroup>, &'static str> {
+
+    RecipientGroup::get_all()
+
+}
+
+#[tauri::command]
+pub fn save_recipient_group(recipient_group: RecipientGroup) -> Result<RecipientGroup, &'static str> {
+
+    recipient_group.save()
+
+}
+
+#[tauri::command]
+pub fn delete_recipient_group(recipient_group_id: i32) -> Result<(), &'static str> {
+
+    RecipientGroup::delete(recipient_group_id)
+
+}
@@ -0,0 +1,127 @@
+use chrono::prelude::*;
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::dal::db;
+use crate::dal::db::user_character_messages::UserCharacterMessages;
+
+#[derive(Serialize, Deserialize)]
+pub struct ScheduledPost {
+    pub scheduled_post_id: i32,
+    pub post_at: DateTime<Utc>,
+    pub retry: bool,
+    pub character_message: UserCharacterMessages
+}
+
+impl ScheduledPost {
+
+    pub fn new(post_at: DateTime<Utc>, retry: bool, character_message: UserCharacterMessages) -> Result<ScheduledPost, &'static str> {
+
+        if character_message.messages.is_empty() {
+            return Err("Scheduled post has no messages");
+        }
+
+        let conn = db::get_connection();
+        const INSERT_QUERY: &str =
+        "
+            INSERT INTO ScheduledPosts (post_at, retry, character_message)
+            VALUES (?1, ?2, ?3)
+            RETURNING scheduled_post_id;
+        ";
+
+        let json = serde_json::to_string(&character_message)
+            .map_err(|_| "Error serializing scheduled post")?;
+
+        let response = conn.query_row(INSERT_QUERY, params![post_at.to_rfc3339(), retry, json], |row| row.get(0));
+        match response {
+            Ok(scheduled_post_id) => Ok(ScheduledPost {
+                scheduled_post_id,
+                post_at,
+                retry,
+                character_message
+            }),
+            Err(_) => Err("Error inserting scheduled post")
+        }
+
+    }
+
+    pub fn get_all() -> Result<Vec<ScheduledPost>, &'static str> {
+
+        let conn = db::get_connection();
+        const SELECT_QUERY: &str =
+        "
+            SELECT
+                scheduled_post_id,
+                post_at,
+                retry,
+                character_message
+            FROM
+                ScheduledPosts
+            ORDER BY
+                post_at ASC,
+                scheduled_post_id ASC;
+        ";
+
+        let mut stmt = match conn.prepare(SELECT_QUERY) {
+            Ok(stmt) => stmt,
+            Err(_) => return Err("Error getting scheduled posts")
+        };
+
+        let scheduled_posts = match stmt.query_map(params![], ScheduledPost::from_row) {
+            Ok(scheduled_posts) => scheduled_posts,
+            Err(_) => return Err("Error getting scheduled posts")
+        };
+
+        // One bad row shouldn't hold up every other schedule.
+        Ok(scheduled_posts
+            .filter_map(|scheduled_post| match scheduled_post {
+                Ok(scheduled_post) => Some(scheduled_post),
+                Err(err) => {
+                    error!("Skipping unreadable scheduled post: {}", err);
+                    None
+                }
+            })
+            .collect())
+
+    }
+
+    /// Returns whether there was anything to delete, so a post can only be taken once.
+    pub fn delete(scheduled_post_id: i32) -> Result<bool, &'static str> {
+
+        let conn = db::get_connection();
+        const DELETE_QUERY: &str =
+        "
+            DELETE FROM
+                ScheduledPosts
+            WHERE
+                scheduled_post_id = ?1;
+        ";
+
+        conn.execute(DELETE_QUERY, params![scheduled_post_id])
+            .map(|deleted| deleted > 0)
+            .map_err(|_| "Error deleting scheduled post")
+
+    }
+
+    fn from_row(row: &Row<'_>) -> Result<ScheduledPost, rusqlite::Error> {
+
+        let post_at: String = row.get(1)?;
+        let post_at = DateTime::parse_from_rfc3339(&post_at)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(e)))?
+            .with_timezone(&Utc);
+
+        let character_message: String = row.get(3)?;
+        let character_message: UserCharacterMessages = serde_json::from_str(&character_message)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e)))?;
+
+        Ok(ScheduledPost {
+            scheduled_post_id: row.get(0)?,
+            post_at,
+            retry: row.get(2)?,
+            character_message
+        })
+
+    }
+
+}
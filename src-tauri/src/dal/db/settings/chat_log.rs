@@ -1,6 +1,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::share::session::QueueSettings;
+
 pub mod window;
 pub mod chat_tab;
 
@@ -16,6 +18,14 @@ pub struct ChatLogSettings {
     #[serde(default = "default_retry_message_submission")]
     pub retry_message_submission: bool,
     pub character_ini_to_pull_from: Option<String>,
+    #[serde(default = "default_capture_stall_timeout_secs")]
+    pub capture_stall_timeout_secs: u64,
+    #[serde(default = "default_record_capture_stream")]
+    pub record_capture_stream: bool,
+    #[serde(default = "QueueSettings::default")]
+    pub capture_queue: QueueSettings,
+    #[serde(default = "default_auto_capture")]
+    pub auto_capture: bool,
 
     #[serde(default = "ChatLogWindow::default")]
     pub window: ChatLogWindow
@@ -34,6 +44,18 @@ pub fn default_log_global_chat() -> bool {
     false
 }
 
+pub fn default_capture_stall_timeout_secs() -> u64 {
+    30
+}
+
+pub fn default_record_capture_stream() -> bool {
+    false
+}
+
+pub fn default_auto_capture() -> bool {
+    false
+}
+
 impl Default for ChatLogSettings {
     
     fn default() -> ChatLogSettings {
@@ -43,6 +65,10 @@ impl Default for ChatLogSettings {
             log_global_chat: false,
             retry_message_submission: false,
             character_ini_to_pull_from: None,
+            capture_stall_timeout_secs: default_capture_stall_timeout_secs(),
+            record_capture_stream: false,
+            capture_queue: QueueSettings::default(),
+            auto_capture: false,
             window: window::ChatLogWindow::default()
         }
 
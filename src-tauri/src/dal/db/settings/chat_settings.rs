@@ -1,6 +1,11 @@
 
 use serde::{Deserialize, Serialize};
 
+pub mod rate_limit;
+pub mod sanitizer;
+
+use rate_limit::RateLimitSettings;
+use sanitizer::SanitizerSettings;
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct ChatSettings {
@@ -24,7 +29,21 @@ pub struct ChatSettings {
     pub starting_characters_are_lowercase: bool,
 
     #[serde(default = "default_show_favourite_emotes")]
-    pub show_favourite_emotes: bool
+    pub show_favourite_emotes: bool,
+
+    #[serde(default = "RateLimitSettings::default")]
+    pub rate_limits: RateLimitSettings,
+
+    #[serde(default = "SanitizerSettings::default")]
+    pub sanitizer: SanitizerSettings,
+
+    /// Added to every part but the last when the backend has to split a message that is too long.
+    #[serde(default = "default_continuation_marker")]
+    pub continuation_marker: String,
+
+    /// A scheduled post found this long after its time, usually because ChaTOR wasn't running, is dropped, not posted.
+    #[serde(default = "default_missed_schedule_after_secs")]
+    pub missed_schedule_after_secs: u64
 
 }
 
@@ -56,6 +75,14 @@ pub fn default_show_favourite_emotes() -> bool {
     true
 }
 
+pub fn default_continuation_marker() -> String {
+    String::new()
+}
+
+pub fn default_missed_schedule_after_secs() -> u64 {
+    300
+}
+
 impl ChatSettings {
 
     pub fn default() -> ChatSettings {
@@ -67,7 +94,11 @@ impl ChatSettings {
             clear_chat_after_posting: false,
             remove_starting_pronouns: false,
             starting_characters_are_lowercase: true,
-            show_favourite_emotes: true
+            show_favourite_emotes: true,
+            rate_limits: RateLimitSettings::default(),
+            sanitizer: SanitizerSettings::default(),
+            continuation_marker: default_continuation_marker(),
+            missed_schedule_after_secs: default_missed_schedule_after_secs()
         }
 
     }
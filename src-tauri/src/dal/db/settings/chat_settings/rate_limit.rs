@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// How quickly parts may be posted to one kind of channel.
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub struct ChannelRateLimit {
+    /// Minimum time between two parts.
+    pub min_interval_ms: u64,
+    /// Parts allowed in any sliding minute. 0 means no limit.
+    pub max_per_minute: u32
+}
+
+/*
+    Global and trade are the quickest to trip the game's spam protection, so they are the most conservative
+    by default. Say and emote keep the 250ms pause posting always had.
+*/
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RateLimitSettings {
+
+    #[serde(default = "default_local")]
+    pub local: ChannelRateLimit,
+    #[serde(default = "default_public")]
+    pub public: ChannelRateLimit,
+    #[serde(default = "default_whisper")]
+    pub whisper: ChannelRateLimit,
+    #[serde(default = "default_group")]
+    pub group: ChannelRateLimit
+
+}
+
+pub fn default_local() -> ChannelRateLimit {
+    ChannelRateLimit { min_interval_ms: 250, max_per_minute: 0 }
+}
+
+pub fn default_public() -> ChannelRateLimit {
+    ChannelRateLimit { min_interval_ms: 2000, max_per_minute: 6 }
+}
+
+pub fn default_whisper() -> ChannelRateLimit {
+    ChannelRateLimit { min_interval_ms: 500, max_per_minute: 20 }
+}
+
+pub fn default_group() -> ChannelRateLimit {
+    ChannelRateLimit { min_interval_ms: 500, max_per_minute: 20 }
+}
+
+impl Default for RateLimitSettings {
+
+    fn default() -> RateLimitSettings {
+
+        RateLimitSettings {
+            local: default_local(),
+            public: default_public(),
+            whisper: default_whisper(),
+            group: default_group()
+        }
+
+    }
+
+}
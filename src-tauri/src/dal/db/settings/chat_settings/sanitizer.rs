@@ -0,0 +1,51 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SanitizerSettings {
+
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Checked before the built in table, longest key first. Keys may be longer than one character.
+    #[serde(default = "default_replacements")]
+    pub replacements: BTreeMap<String, String>,
+    /// Typed in place of anything the game can't show and nothing else covers. Empty drops it.
+    #[serde(default = "default_unsupported_replacement")]
+    pub unsupported_replacement: String,
+    /// Types everything as is, as UTF-16, for clients that can render it.
+    #[serde(default = "default_pass_through_unicode")]
+    pub pass_through_unicode: bool
+
+}
+
+pub fn default_enabled() -> bool {
+    true
+}
+
+pub fn default_replacements() -> BTreeMap<String, String> {
+    BTreeMap::new()
+}
+
+pub fn default_unsupported_replacement() -> String {
+    String::new()
+}
+
+pub fn default_pass_through_unicode() -> bool {
+    false
+}
+
+impl Default for SanitizerSettings {
+
+    fn default() -> SanitizerSettings {
+
+        SanitizerSettings {
+            enabled: true,
+            replacements: default_replacements(),
+            unsupported_replacement: default_unsupported_replacement(),
+            pass_through_unicode: false
+        }
+
+    }
+
+}
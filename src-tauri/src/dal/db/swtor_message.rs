@@ -48,14 +48,21 @@ impl SwtorMessage {
         
     }
 
+    /// Decodes the HTML entities the game escapes, numeric ones included. &amp; goes last so nothing is decoded twice.
     pub fn get_parsed_message(&self) -> String {
 
         self.message
             .replace("&quot;", "\"")
+            .replace("&#34;", "\"")
             .replace("&lt;", "<")
+            .replace("&#60;", "<")
             .replace("&gt;", ">")
-            .replace("&amp;", "&")
+            .replace("&#62;", ">")
             .replace("&apos;", "'")
+            .replace("&#39;", "'")
+            .replace("&#039;", "'")
+            .replace("&amp;", "&")
+            .replace("&#38;", "&")
 
     }
 
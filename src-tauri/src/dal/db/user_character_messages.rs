@@ -7,6 +7,44 @@ use tracing::error;
 use crate::dal::db;
 use crate::utils::StringUtils;
 
+/// What the game's chat box takes in one line, for any command not listed below.
+pub const GAME_MESSAGE_MAXIMUM: usize = 255;
+
+/*
+    Per channel limits, keyed on the command without its slash. Every channel we have measured takes the
+    same 255, so they differ only in how much of it the command or whisper prefix uses up, but a channel
+    that turns out to be shorter only needs its entry changed here.
+*/
+const CHANNEL_MESSAGE_MAXIMUMS: &[(&[&str], usize)] = &[
+    (&["s", "say", "y", "yell", "e", "emote", "me"], 255),
+    (&["w", "whisper", "t", "tell", "r", "reply"], 255),
+    (&["p", "party", "group", "ops", "op", "g", "guild", "o", "officer"], 255),
+    (&["gen", "general", "trade", "pvp"], 255)
+];
+
+/// How many characters the game takes in one line posted with `command`, prefix included.
+pub fn game_message_maximum(command: Option<&str>) -> usize {
+
+    let command = match command {
+        Some(command) => command,
+        None => return GAME_MESSAGE_MAXIMUM
+    };
+
+    let name = command
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("/")
+        .to_lowercase();
+
+    CHANNEL_MESSAGE_MAXIMUMS
+        .iter()
+        .find(|(commands, _)| commands.contains(&name.as_str()))
+        .map(|(_, maximum)| *maximum)
+        .unwrap_or(GAME_MESSAGE_MAXIMUM)
+
+}
+
 pub struct CommandMessage {
     pub command: Option<String>,
     pub message: String
@@ -37,6 +75,17 @@ impl CommandMessage {
         
     }
 
+    /// Characters the command and the space after it take up once concatenated.
+    pub fn prefix_length(&self) -> usize {
+
+        match self.command.as_ref() {
+            Some(command) if !self.message.is_empty() => command.chars().count() + 1,
+            Some(command) => command.chars().count(),
+            None => 0
+        }
+
+    }
+
     pub fn is_command_only(&self) -> bool {
         self.command.is_some() && self.message.len() == 0
     }
@@ -117,6 +166,32 @@ impl UserCharacterMessages {
         
     }
 
+    /// The name of the character posting, if it has been seen in chat before.
+    pub fn get_character_name(&self) -> Option<String> {
+
+        let character_id = self.character_id?;
+        let conn = db::get_connection();
+
+        const SELECT_CHARACTER_NAME: &str = 
+        "
+            SELECT 
+                character_name
+            FROM
+                Characters
+            WHERE
+                character_id = ?1;
+        ";
+
+        match conn.query_row(SELECT_CHARACTER_NAME, params![character_id], |row| row.get(0)) {
+            Ok(character_name) => Some(character_name),
+            Err(err) => {
+                error!("UserCharacterMessages::get_character_name() - Error finding character {}: {}", character_id, err);
+                None
+            }
+        }
+
+    }
+
     pub fn prepare_messages(&mut self) {
 
         self.messages.iter_mut().for_each(|message| {
@@ -129,6 +204,56 @@ impl UserCharacterMessages {
 
     }
 
+    /*
+        Splits any message that won't fit on one line into several, on sentence boundaries where possible and
+        on words otherwise, each keeping the original command. `continuation_marker` is added to the end of
+        every part but the last. Returns the index of the original message each fitted one came from.
+    */
+    pub fn fit_to_game_limits(&mut self, continuation_marker: &str) -> Result<Vec<usize>, &'static str> {
+
+        let mut fitted: Vec<String> = Vec::new();
+        let mut origins: Vec<usize> = Vec::new();
+        for (origin, message) in self.messages.iter().enumerate() {
+
+            // Only the length is checked here, so a message with two whispers is typed as it always was.
+            let (command_message, _) = split_command(message)?;
+            let maximum = game_message_maximum(command_message.command.as_deref());
+            if command_message.concat().chars().count() <= maximum {
+                fitted.push(message.clone());
+                origins.push(origin);
+                continue;
+            }
+
+            let marker = match continuation_marker.is_empty() {
+                true => String::new(),
+                false => format!(" {}", continuation_marker.trim())
+            };
+
+            let reserved = command_message.prefix_length() + marker.chars().count();
+            if reserved >= maximum {
+                return Err("The command leaves no room for a message");
+            }
+
+            let parts = split_on_boundaries(&command_message.message, maximum - reserved)?;
+            let last = parts.len() - 1;
+            for (index, part) in parts.into_iter().enumerate() {
+
+                let part = match index == last {
+                    true => part,
+                    false => format!("{}{}", part, marker)
+                };
+                fitted.push(CommandMessage::new(command_message.command.clone(), part).concat());
+                origins.push(origin);
+
+            }
+
+        }
+
+        self.messages = fitted;
+        Ok(origins)
+
+    }
+
     pub fn get_all_command_message_splits(&self) -> Result<Vec<CommandMessage>, &'static str> {
 
         let mut c_and_m: Vec<CommandMessage> = Vec::new();
@@ -143,29 +268,222 @@ impl UserCharacterMessages {
 
     fn get_command_message_split(&self, message: &str) -> Result<CommandMessage, &'static str> {
 
-        if !message.starts_with("/") {
-            return Ok(CommandMessage::new(None, message.to_string()));
+        let (command_message, whispers) = split_command(message)?;
+        if whispers > 1 {
+            return Err("Must have one whisper in a message, broadcast it to whisper several characters");
         }
 
-        let whisper_re = Regex::new(r"(\/w\s+|\/whisper\s+)([^:]+):").unwrap();
-        let whispers: Vec<&str> = whisper_re.captures_iter(message).map(|c| c.get(0).unwrap().as_str()).collect();
+        Ok(command_message)
+
+    }
+
+}
+
+/// Splits the command off `message`, along with how many whispers it contains.
+fn split_command(message: &str) -> Result<(CommandMessage, usize), &'static str> {
+
+    if !message.starts_with("/") {
+        return Ok((CommandMessage::new(None, message.to_string()), 0));
+    }
+
+    let whisper_re = Regex::new(r"(\/w\s+|\/whisper\s+)([^:]+):").unwrap();
+    let whispers: Vec<&str> = whisper_re.captures_iter(message).map(|c| c.get(0).unwrap().as_str()).collect();
+
+    if let Some(whisper) = whispers.first() {
+        let command_message = CommandMessage::new(Some(whisper.to_string()), message.replace(whisper, "").trim().to_string());
+        return Ok((command_message, whispers.len()));
+    }
+
+    // No whisper was captured, so it must be a simple command
+    let simple_re = Regex::new(r"^\/([a-zA-Z0-9]+)").unwrap();
+    let simple_command = simple_re
+        .captures(message)
+        .and_then(|captures| captures.get(0))
+        .ok_or("Unrecognised command")?
+        .as_str();
 
-        if whispers.len() > 1 {
+    Ok((CommandMessage::new(Some(simple_command.to_string()), message.replace(simple_command, "").trim().to_string()), 0))
 
-            return Err("Must have one whisper in a message");
+}
+
+/// Greedily packs sentences, or words where a sentence is too long, into parts of at most `limit` characters.
+fn split_on_boundaries(text: &str, limit: usize) -> Result<Vec<String>, &'static str> {
+
+    let sentence_re = Regex::new(r#"[^.!?]*[.!?]+["')\]]*|[^.!?]+"#).unwrap();
+
+    let mut pieces: Vec<&str> = Vec::new();
+    for sentence in sentence_re.find_iter(text).map(|m| m.as_str().trim()).filter(|s| !s.is_empty()) {
 
-        } else if whispers.len() == 1 {
+        if sentence.chars().count() <= limit {
+            pieces.push(sentence);
+        } else {
+            pieces.extend(sentence.split_whitespace());
+        }
+
+    }
 
-            let whisper = whispers[0];
-            return Ok(CommandMessage::new(Some(whisper.to_string()), message.replace(whisper, "").trim().to_string()));
+    let mut parts: Vec<String> = Vec::new();
+    let mut buffer = String::new();
+    for piece in pieces {
 
+        if piece.chars().count() > limit {
+            return Err("A word is too long to fit in a single post");
         }
 
-        // No whisper was captured, so it must be a simple command
-        let simple_re = Regex::new(r"^\/([a-zA-Z0-9]+)").unwrap();
-        let simple_command = simple_re.captures(message).unwrap().get(0).unwrap().as_str();
+        if !buffer.is_empty() && buffer.chars().count() + 1 + piece.chars().count() > limit {
+            parts.push(std::mem::take(&mut buffer));
+        }
+
+        if !buffer.is_empty() {
+            buffer.push(' ');
+        }
+        buffer.push_str(piece);
+
+    }
+
+    if !buffer.is_empty() {
+        parts.push(buffer);
+    }
+
+    if parts.is_empty() {
+        return Err("No messages to submit");
+    }
+
+    Ok(parts)
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn messages(messages: &[&str]) -> UserCharacterMessages {
+
+        UserCharacterMessages {
+            message_type: MessageType::ChatMessage,
+            character_id: None,
+            messages: messages.iter().map(|message| message.to_string()).collect()
+        }
+
+    }
+
+    fn words(count: usize) -> String {
+
+        vec!["word"; count].join(" ")
+
+    }
+
+    #[test]
+    fn messages_that_fit_are_left_alone() {
+
+        let mut character_message = messages(&["Hello there.", "/e waves."]);
+        assert_eq!(character_message.fit_to_game_limits("(...)"), Ok(vec![0, 1]));
+        assert_eq!(character_message.messages, vec!["Hello there.", "/e waves."]);
+
+    }
+
+    #[test]
+    fn prefix_counts_toward_the_limit() {
+
+        // 251 characters fit on their own, but not behind "/e ".
+        let text = format!("{}.", "a".repeat(250));
+        let mut plain = messages(&[&text]);
+        plain.fit_to_game_limits("").unwrap();
+        assert_eq!(plain.messages.len(), 1);
+
+        let mut emote = messages(&[&format!("/e {} {}", text, text)]);
+        emote.fit_to_game_limits("").unwrap();
+        assert_eq!(emote.messages, vec![format!("/e {}", text), format!("/e {}", text)]);
+        assert!(emote.messages.iter().all(|message| message.chars().count() <= GAME_MESSAGE_MAXIMUM));
+
+        let whisper = CommandMessage::new(Some("/w Kaelyn:".to_string()), "hi".to_string());
+        assert_eq!(whisper.prefix_length(), 11);
+        assert_eq!(CommandMessage::new(Some("/roll".to_string()), String::new()).prefix_length(), 5);
+
+    }
+
+    #[test]
+    fn splits_on_sentences() {
+
+        let first = format!("{}.", "a".repeat(150));
+        let second = format!("{}!", "b".repeat(150));
+        let mut character_message = messages(&[&format!("{} {}", first, second)]);
+
+        assert_eq!(character_message.fit_to_game_limits(""), Ok(vec![0, 0]));
+        assert_eq!(character_message.messages, vec![first, second]);
+
+    }
+
+    #[test]
+    fn falls_back_to_words_for_long_sentences() {
+
+        let mut character_message = messages(&[&words(60)]);
+        character_message.fit_to_game_limits("").unwrap();
+
+        assert_eq!(character_message.messages.len(), 2);
+        assert!(character_message.messages.iter().all(|message| message.chars().count() <= GAME_MESSAGE_MAXIMUM));
+        assert_eq!(character_message.messages.join(" "), words(60));
+
+    }
+
+    #[test]
+    fn marks_every_part_but_the_last() {
+
+        let mut character_message = messages(&[&format!("/e {}", words(60))]);
+        character_message.fit_to_game_limits("(...)").unwrap();
+
+        let parts = &character_message.messages;
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].starts_with("/e ") && parts[0].ends_with(" (...)"));
+        assert!(parts[0].chars().count() <= GAME_MESSAGE_MAXIMUM);
+        assert!(parts[1].starts_with("/e ") && !parts[1].ends_with("(...)"));
+
+    }
+
+    #[test]
+    fn reports_when_the_prefix_leaves_no_room() {
+
+        let recipient = "a".repeat(GAME_MESSAGE_MAXIMUM);
+        let mut character_message = messages(&[&format!("/w {}: {}", recipient, words(5))]);
+
+        assert_eq!(character_message.fit_to_game_limits(""), Err("The command leaves no room for a message"));
+
+    }
+
+    #[test]
+    fn reports_words_that_cannot_fit() {
+
+        assert_eq!(split_on_boundaries(&"a".repeat(20), 10), Err("A word is too long to fit in a single post"));
+
+    }
+
+    #[test]
+    fn rejects_unrecognised_commands() {
+
+        for message in ["/ hi", "/:)", "/é"] {
+            assert_eq!(messages(&[message]).fit_to_game_limits("").err(), Some("Unrecognised command"));
+            assert!(messages(&[message]).get_all_command_message_splits().is_err());
+        }
+
+    }
+
+    #[test]
+    fn only_splitting_into_commands_rejects_two_whispers() {
+
+        let mut character_message = messages(&["/w Kaelyn: hi /w Torven: hi"]);
+        assert!(character_message.get_all_command_message_splits().is_err());
+        assert_eq!(character_message.fit_to_game_limits(""), Ok(vec![0]));
+
+    }
+
+    #[test]
+    fn looks_up_limits_by_command() {
 
-        return Ok(CommandMessage::new(Some(simple_command.to_string()), message.replace(simple_command, "").trim().to_string()));
+        assert_eq!(game_message_maximum(None), GAME_MESSAGE_MAXIMUM);
+        assert_eq!(game_message_maximum(Some("/E")), 255);
+        assert_eq!(game_message_maximum(Some("/w Kaelyn:")), 255);
+        assert_eq!(game_message_maximum(Some("/unknown")), GAME_MESSAGE_MAXIMUM);
 
     }
 
@@ -0,0 +1,61 @@
+use serde::Deserialize;
+
+use crate::share::handshake::HookOffsets;
+use crate::share::signature::{BytePattern, HookSignatures};
+
+const PATH: &str = "game_signatures.toml";
+
+#[derive(Deserialize, Debug)]
+pub struct GameBuild {
+    pub checksum: String,
+    pub offsets: HookOffsets
+}
+
+/*
+    Every swtor.exe build ChaTOR knows how to hook. Read from disk each time capture starts, so supporting a
+    new patch is a matter of editing game_signatures.toml. Builds that aren't listed can still be hooked if
+    the signatures locate the functions.
+*/
+#[derive(Deserialize, Debug)]
+pub struct SignatureManifest {
+    /// Byte patterns the DLL scans swtor.exe for when a build has no offset for a hook.
+    #[serde(default)]
+    pub signatures: HookSignatures,
+    #[serde(default)]
+    pub builds: Vec<GameBuild>
+}
+
+impl SignatureManifest {
+
+    pub fn load() -> Result<SignatureManifest, String> {
+
+        let contents = std::fs::read_to_string(PATH)
+            .map_err(|e| format!("Error reading {}: {}", PATH, e))?;
+
+        let manifest: SignatureManifest = toml::from_str(&contents)
+            .map_err(|e| format!("Error parsing {}: {}", PATH, e))?;
+
+        manifest.validate()?;
+        Ok(manifest)
+
+    }
+
+    /// Catches malformed patterns here rather than inside the game process.
+    fn validate(&self) -> Result<(), String> {
+
+        for pattern in [&self.signatures.chat, &self.signatures.friends_list].into_iter().flatten() {
+            BytePattern::parse(pattern).map_err(|e| format!("{}: {}", PATH, e))?;
+        }
+        Ok(())
+
+    }
+
+    pub fn find_build(&self, checksum: &str) -> Option<&GameBuild> {
+
+        self.builds
+            .iter()
+            .find(|build| build.checksum.eq_ignore_ascii_case(checksum))
+
+    }
+
+}
@@ -6,30 +6,39 @@ mod utils;
 mod lib_only;
 
 use std::io::prelude::*;
-use std::net::TcpListener;
 use std::net::TcpStream;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use std::str;
 use std::thread;
 use std::time::Duration;
 
-use lib_only::chat_message;
+use lib_only::{chat_message, friends_list, hook_resolver};
 
 use share::CaptureMessage;
-
+use share::handshake::{CaptureHello, HookOffsets};
+use share::auth;
+use share::codec::FrameDecoder;
+use share::control::{ControlAck, ControlMessage, ControlReply, ControlRequest};
+use share::session::CaptureSession;
+use share::health::HEARTBEAT_INTERVAL;
+
+use windows::Win32::Foundation::HMODULE;
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
 use windows::core::PCSTR;
 
-use lib_only::{submit_message, drain_messages};
+use lib_only::{submit_message, drain_messages, queue_depth, set_capture_paused, set_channel_filter, stats};
+use lib_only::{configure_queue, report_drops, total_dropped};
 
 #[macro_use]
 extern crate lazy_static;
 
 lazy_static! {
     static ref QUIT: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+    static ref SESSION: Arc<Mutex<Option<CaptureSession>>> = Arc::new(Mutex::new(None));
+    static ref RESOLVED_OFFSETS: Arc<Mutex<Option<HookOffsets>>> = Arc::new(Mutex::new(None));
 }
 
 
@@ -41,7 +50,7 @@ fn detour_init() {
     }
 
     set_panic_hook();
-    start_quit_listener();
+    start_heartbeat();
 
     unsafe {
         begin_hook();
@@ -53,6 +62,64 @@ fn should_quit() -> bool {
     QUIT.load(Ordering::Relaxed)
 }
 
+fn capture_port() -> u16 {
+
+    SESSION
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|session| session.capture_port)
+        .unwrap_or_default()
+
+}
+
+/*
+    Offsets from the session, with any the session left out found by signature scanning. Resolved once, as
+    the game image doesn't change while we're loaded.
+*/
+fn hook_offsets() -> HookOffsets {
+
+    let mut resolved = RESOLVED_OFFSETS.lock().unwrap();
+    if let Some(offsets) = resolved.as_ref() {
+        return offsets.clone();
+    }
+
+    let (offsets, signatures) = match SESSION.lock().unwrap().as_ref() {
+        Some(session) => (session.offsets.clone(), session.signatures.clone()),
+        None => return HookOffsets::default()
+    };
+
+    let offsets = match swtor_module() {
+        Some(hmodule) => hook_resolver::resolve(hmodule, &offsets, &signatures),
+        None => offsets
+    };
+
+    resolved.replace(offsets.clone());
+    offsets
+
+}
+
+fn secret() -> String {
+
+    SESSION
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|session| session.secret.clone())
+        .unwrap_or_default()
+
+}
+
+/// The session is re-read on every connection attempt, as the app writes a new one each time it injects.
+fn refresh_session() -> Result<(), &'static str> {
+
+    let session = CaptureSession::read()?;
+    configure_queue(session.queue.clone());
+    SESSION.lock().unwrap().replace(session);
+    Ok(())
+
+}
+
 fn start_tcp_messager() -> Result<(), &'static str> {
 
     let stream_connector = || -> Result<TcpStream, &'static str> {
@@ -63,10 +130,24 @@ fn start_tcp_messager() -> Result<(), &'static str> {
                 return Err("Quitting");
             }
 
-            match TcpStream::connect("127.0.0.1:4592") {
-                Ok(stream) => { return Ok(stream); },
-                Err(_) => { thread::sleep(Duration::from_millis(1000)); }
+            if let Err(_) = refresh_session() {
+                thread::sleep(Duration::from_millis(1000));
+                continue;
+            }
+
+            match TcpStream::connect(("127.0.0.1", capture_port())) {
+                Ok(mut stream) => {
+
+                    // The app refuses to read anything until it knows which build it is talking to.
+                    if let Ok(_) = stream.write_all(&hello().as_frame(&secret())) {
+                        start_control_reader(&stream);
+                        return Ok(stream);
+                    }
+
+                },
+                Err(_) => {}
             }
+            thread::sleep(Duration::from_millis(1000));
 
         }
 
@@ -78,13 +159,13 @@ fn start_tcp_messager() -> Result<(), &'static str> {
 
         loop {
 
-            if should_quit() {
-                break;
-            }
+            // Drain one last time after quitting, so the acknowledgement of the Quit makes it out.
+            let quitting = should_quit();
+            report_drops();
 
             for message in drain_messages() {
 
-                if let Err(_) = stream.write(message.as_bytes()) {
+                if let Err(_) = stream.write_all(&auth::encode_signed_json(&secret(), &message)) {
 
                     if should_quit() {
                         break;
@@ -96,11 +177,15 @@ fn start_tcp_messager() -> Result<(), &'static str> {
                         return;
                     }
 
-                    stream.write(message.as_bytes()).unwrap();
+                    stream.write_all(&auth::encode_signed_json(&secret(), &message)).unwrap();
 
                 }
 
             }
+
+            if quitting {
+                break;
+            }
             thread::sleep(Duration::from_millis(100));
 
         }
@@ -110,6 +195,28 @@ fn start_tcp_messager() -> Result<(), &'static str> {
 
 }
 
+/// Lets the app notice when the payload has silently stopped, e.g. when the detour no longer fires after a patch.
+fn start_heartbeat() {
+
+    thread::spawn(|| {
+
+        while !should_quit() {
+
+            thread::sleep(HEARTBEAT_INTERVAL);
+            submit_message(CaptureMessage::Heartbeat(stats::snapshot(queue_depth(), total_dropped())));
+
+        }
+
+    });
+
+}
+
+fn hello() -> CaptureMessage {
+
+    CaptureMessage::Hello(CaptureHello::new(hook_offsets()))
+
+}
+
 fn set_panic_hook() {
 
     std::panic::set_hook(Box::new(|panic_info| {
@@ -118,31 +225,111 @@ fn set_panic_hook() {
 
 }
 
-fn start_quit_listener() {
+/*
+    Reads ControlRequests the app sends back over the capture connection. Every request is acknowledged
+    through the regular message queue with the id it was sent with.
+*/
+fn start_control_reader(stream: &TcpStream) {
 
-    thread::spawn(|| {
+    let mut stream = match stream.try_clone() {
+        Ok(stream) => stream,
+        Err(_) => {
+            submit_message(CaptureMessage::CaptureError("Failed to open control channel".to_string()));
+            return;
+        }
+    };
+
+    thread::spawn(move || {
 
-        let listener = TcpListener::bind("127.0.0.1:4593").unwrap();
-        listener.accept().unwrap();
+        let mut decoder = FrameDecoder::new();
+        let mut buffer: [u8; 512] = [0; 512];
+        while !should_quit() {
 
-        end_detours();
+            match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => decoder.push(&buffer[..read])
+            }
 
-        QUIT.store(true, Ordering::Relaxed);
+            while let Some(frame) = decoder.next_frame() {
+
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(_) => {
+                        submit_message(CaptureMessage::CaptureError("Control channel out of sync, closing it".to_string()));
+                        return;
+                    }
+                };
+
+                match ControlRequest::from_frame(&secret(), &frame) {
+                    Ok(request) => {
+                        handle_control_request(request);
+                    },
+                    Err(_) => {
+                        submit_message(CaptureMessage::CaptureError("Rejected unauthenticated control request".to_string()));
+                    }
+                }
+
+            }
+
+        }
 
     });
 
+}
+
+fn handle_control_request(request: ControlRequest) {
+
+    let quit = matches!(request.message, ControlMessage::Quit);
+    let reply = match request.message {
+        ControlMessage::Quit => {
+            end_detours();
+            ControlReply::Ack
+        },
+        ControlMessage::Pause => {
+            set_capture_paused(true);
+            ControlReply::Ack
+        },
+        ControlMessage::Resume => {
+            set_capture_paused(false);
+            ControlReply::Ack
+        },
+        ControlMessage::Ping(nonce) => {
+            ControlReply::Pong(nonce)
+        },
+        ControlMessage::SetChannelFilter(channels) => {
+            set_channel_filter(channels);
+            ControlReply::Ack
+        }
+    };
+
+    submit_message(CaptureMessage::ControlAck(ControlAck {
+        id: request.id,
+        reply
+    }));
+
+    if quit {
+        QUIT.store(true, Ordering::Relaxed);
+    }
+
+}
+
+fn swtor_module() -> Option<HMODULE> {
+
+    unsafe {
+        GetModuleHandleA(PCSTR(b"swtor.exe\0".as_ptr())).ok()
+    }
 
 }
 
 unsafe fn begin_hook() {
 
-    match GetModuleHandleA(PCSTR(b"swtor.exe\0".as_ptr())) {
-        Ok(hmodule) => {
+    match swtor_module() {
+        Some(hmodule) => {
             submit_message(CaptureMessage::Info("Found module".to_string()));
             submit_message(CaptureMessage::Info(format!("Module handle: {:?}", hmodule)));
             begin_detours(hmodule.0);
         },
-        Err(_) => {
+        None => {
             submit_message(CaptureMessage::Info("Failed to find module".to_string()));
         }
     }
@@ -151,12 +338,23 @@ unsafe fn begin_hook() {
 
 fn begin_detours(base_address: isize) {
 
-    chat_message::begin_detour(base_address);
+    let offsets = hook_offsets();
+    match offsets.chat {
+        Some(offset) => chat_message::begin_detour(base_address, offset),
+        None => submit_message(CaptureMessage::CaptureError("No chat message offset for this game build".to_string()))
+    }
+
+    /* Presence is a nice to have, so a missing offset is only worth a note. */
+    match offsets.friends_list {
+        Some(offset) => friends_list::begin_detour(base_address, offset),
+        None => submit_message(CaptureMessage::Info("No friends list offset for this game build".to_string()))
+    }
 
 }
 
 fn end_detours() {
 
     chat_message::end_detour();
+    friends_list::end_detour();
 
 }
\ No newline at end of file
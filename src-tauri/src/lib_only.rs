@@ -1,25 +1,94 @@
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crate::share::CaptureMessage;
+use crate::share::health::DropReport;
+use crate::share::session::QueueSettings;
 
 pub mod chat_message;
 pub mod friends_list;
+pub mod stats;
+pub mod message_queue;
+pub mod hook_resolver;
+
+use message_queue::MessageQueue;
 
 lazy_static! {
-    static ref MESSAGES: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    static ref MESSAGES: Arc<Mutex<MessageQueue>> = Arc::new(Mutex::new(MessageQueue::new(QueueSettings::default())));
+    static ref CAPTURE_PAUSED: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+    static ref CHANNEL_FILTER: Arc<Mutex<Option<Vec<i32>>>> = Arc::new(Mutex::new(None));
 }
 
 pub fn submit_message(capture_message: CaptureMessage) {
 
     let mut messages = MESSAGES.lock().unwrap();
-    messages.push(capture_message.as_json_str());
+    messages.push(&capture_message);
+
+}
+
+pub fn configure_queue(settings: QueueSettings) {
+
+    MESSAGES.lock().unwrap().configure(settings);
+
+}
+
+pub fn total_dropped() -> DropReport {
+
+    MESSAGES.lock().unwrap().total_dropped()
+
+}
+
+/// Queues a MessagesDropped if anything was dropped since the last call.
+pub fn report_drops() {
+
+    let report = MESSAGES.lock().unwrap().take_unreported_drops();
+    if let Some(report) = report {
+        submit_message(CaptureMessage::MessagesDropped(report));
+    }
+
+}
+
+pub fn queue_depth() -> usize {
+
+    MESSAGES.lock().unwrap().len()
 
 }
 
 pub fn drain_messages() -> Vec<String> {
 
     let mut messages = MESSAGES.lock().unwrap();
-    messages.drain(..).collect()
+    messages.drain()
 
-}
\ No newline at end of file
+}
+
+pub fn set_capture_paused(paused: bool) {
+
+    CAPTURE_PAUSED.store(paused, Ordering::Relaxed);
+
+}
+
+pub fn is_capture_paused() -> bool {
+
+    CAPTURE_PAUSED.load(Ordering::Relaxed)
+
+}
+
+pub fn set_channel_filter(channels: Option<Vec<i32>>) {
+
+    *CHANNEL_FILTER.lock().unwrap() = channels;
+
+}
+
+pub fn should_capture(channel_id: i32) -> bool {
+
+    if is_capture_paused() {
+        return false;
+    }
+
+    match CHANNEL_FILTER.lock().unwrap().as_ref() {
+        Some(channels) => channels.contains(&channel_id),
+        None => true
+    }
+
+}
@@ -2,21 +2,19 @@
 use std::mem;
 use retour::static_detour;
 
-use crate::lib_only::submit_message;
+use crate::lib_only::{should_capture, stats, submit_message};
 use crate::share::raw_swtor_message::RawSwtorMessage;
 use crate::share::CaptureMessage;
 
-const CHAT_RELATIVE_ADDRESS: isize = 0x03f3740;
-
 static_detour! {
     static ChatHook: extern "C" fn(*mut u64, *const *const i8, *const *const i8, i32, *const *const i8) -> i64;
 }
 
-pub fn begin_detour(base_address: isize) {
+pub fn begin_detour(base_address: isize, offset: isize) {
 
     unsafe {
 
-        let target: extern "C" fn(*mut u64, *const *const i8, *const *const i8, i32, *const *const i8) -> i64 = mem::transmute(base_address + CHAT_RELATIVE_ADDRESS);
+        let target: extern "C" fn(*mut u64, *const *const i8, *const *const i8, i32, *const *const i8) -> i64 = mem::transmute(base_address + offset);
         match ChatHook.initialize(target, receive_chat_message_detour) {
             Ok(_) => {
                 submit_message(CaptureMessage::Info("Chat Message detour initialized".to_string()));
@@ -33,11 +31,18 @@ pub fn begin_detour(base_address: isize) {
 
 pub fn receive_chat_message_detour(param_1: *mut u64, from: *const *const i8, to: *const *const i8, channel_id: i32, chat_message: *const *const i8) -> i64 {
 
-    match RawSwtorMessage::from_raw_ptrs(channel_id, from, to, chat_message) {
-        Ok(message) => {
-            submit_message(CaptureMessage::Chat(message));
-        },
-        Err(_) => {}
+    if should_capture(channel_id) {
+
+        match RawSwtorMessage::from_raw_ptrs(channel_id, from, to, chat_message) {
+            Ok(message) => {
+                stats::record_captured();
+                submit_message(CaptureMessage::Chat(message));
+            },
+            Err(_) => {
+                stats::record_conversion_failure();
+            }
+        }
+
     }
 
     return ChatHook.call(param_1, from, to, channel_id, chat_message);
@@ -46,8 +51,17 @@ pub fn receive_chat_message_detour(param_1: *mut u64, from: *const *const i8, to
 
 pub fn end_detour() {
 
+    // Never initialized when the chat offset couldn't be resolved.
+    if !ChatHook.is_enabled() {
+        return;
+    }
+
     unsafe {
-        ChatHook.disable().unwrap();
+
+        if ChatHook.disable().is_err() {
+            submit_message(CaptureMessage::CaptureError("Failed to disable chat message detour".to_string()));
+        }
+
     }
 
 }
\ No newline at end of file
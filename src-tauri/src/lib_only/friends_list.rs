@@ -1,9 +1,9 @@
-
 use std::mem;
 use std::ffi::CStr;
 
 use retour::static_detour;
 
+use crate::lib_only::{is_capture_paused, submit_message};
 use crate::share::CaptureMessage;
 
 type UpdateFriendsListHookType = extern "C" fn(*const u64, *const i8, i8, *const u64) -> i64;
@@ -12,21 +12,44 @@ static_detour! {
     static UpdateFriendsListHook: extern "C" fn(*const u64, *const i8, i8, *const u64) -> i64;
 }
 
-const UPDATE_FRIENDS_LIST_ADDRESS: isize = 0x03f3b80;
+const LOGGED_IN: i8  = 2;
+const LOGGED_OUT: i8 = 1;
 
-pub unsafe fn begin_detour(base_address: isize) {
+pub fn begin_detour(base_address: isize, offset: isize) {
 
-    let target: UpdateFriendsListHookType = mem::transmute(base_address + UPDATE_FRIENDS_LIST_ADDRESS);
-    match UpdateFriendsListHook.initialize(target, update_friends_list_detour) {
-        Ok(_) => {},
-        Err(_) => {
+    unsafe {
 
+        let target: UpdateFriendsListHookType = mem::transmute(base_address + offset);
+        match UpdateFriendsListHook.initialize(target, update_friends_list_detour) {
+            Ok(_) => {
+                submit_message(CaptureMessage::Info("Friends list detour initialized".to_string()));
+                if UpdateFriendsListHook.enable().is_err() {
+                    submit_message(CaptureMessage::CaptureError("Failed to enable friends list detour".to_string()));
+                }
+            },
+            Err(_) => {
+                submit_message(CaptureMessage::CaptureError("Failed to initialize friends list detour".to_string()));
+            }
         }
+
     }
 
 }
 
-pub unsafe fn end_detour() {
+pub fn end_detour() {
+
+    // Optional, so it may never have been initialized.
+    if !UpdateFriendsListHook.is_enabled() {
+        return;
+    }
+
+    unsafe {
+
+        if UpdateFriendsListHook.disable().is_err() {
+            submit_message(CaptureMessage::CaptureError("Failed to disable friends list detour".to_string()));
+        }
+
+    }
 
 }
 
@@ -34,12 +57,19 @@ fn update_friends_list_detour(param_1: *const u64, character: *const i8, login_c
 
     unsafe {
 
-        // Sometimes t_character is empty. Perhaps the user hasn't fetched the friends list yet?
-        if let Ok(character_name) = CStr::from_ptr(character).to_str() {
+        // Sometimes character is null or empty. Perhaps the user hasn't fetched the friends list yet?
+        if !character.is_null() && !is_capture_paused() && (login_code == LOGGED_IN || login_code == LOGGED_OUT) {
 
-            // 2 for logged in, 1 for logged out
-            let logged_in: bool = login_code == 2;
-            todo!("UpdateFriendsListHook");
+            if let Ok(character_name) = CStr::from_ptr(character).to_str() {
+
+                if !character_name.is_empty() {
+                    submit_message(CaptureMessage::FriendStatus {
+                        character: character_name.to_string(),
+                        online: login_code == LOGGED_IN
+                    });
+                }
+
+            }
 
         }
 
@@ -47,4 +77,4 @@ fn update_friends_list_detour(param_1: *const u64, character: *const i8, login_c
 
     }
 
-}
\ No newline at end of file
+}
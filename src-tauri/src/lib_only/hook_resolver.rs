@@ -0,0 +1,132 @@
+use std::mem;
+use std::slice;
+
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::System::ProcessStatus::{GetModuleInformation, MODULEINFO};
+use windows::Win32::System::Threading::GetCurrentProcess;
+
+use crate::lib_only::submit_message;
+use crate::share::handshake::HookOffsets;
+use crate::share::signature::{BytePattern, HookSignatures, ScanError};
+use crate::share::CaptureMessage;
+
+/// The headers, section table included, always fit in the first page.
+const PE_HEADER_LIMIT: usize = 0x1000;
+const SECTION_HEADER_SIZE: usize = 40;
+
+/*
+    Fills in any hook without an offset by scanning the loaded swtor.exe image for its signature.
+*/
+pub fn resolve(hmodule: HMODULE, offsets: &HookOffsets, signatures: &HookSignatures) -> HookOffsets {
+
+    let needs_scan = (offsets.chat.is_none() && signatures.chat.is_some())
+        || (offsets.friends_list.is_none() && signatures.friends_list.is_some());
+
+    if !needs_scan {
+        return offsets.clone();
+    }
+
+    let code = match unsafe { module_code(hmodule) } {
+        Some(code) => code,
+        None => {
+            submit_message(CaptureMessage::CaptureError("Failed to find swtor.exe's code section".to_string()));
+            return offsets.clone();
+        }
+    };
+
+    HookOffsets {
+        chat: offsets.chat.or_else(|| scan("chat", &code, &signatures.chat)),
+        friends_list: offsets.friends_list.or_else(|| scan("friends list", &code, &signatures.friends_list))
+    }
+
+}
+
+/// The executable section of the module, and where it starts relative to the module base.
+struct ModuleCode {
+    bytes: &'static [u8],
+    offset: usize
+}
+
+fn scan(name: &str, code: &ModuleCode, signature: &Option<String>) -> Option<isize> {
+
+    let pattern = match BytePattern::parse(signature.as_ref()?) {
+        Ok(pattern) => pattern,
+        Err(err) => {
+            submit_message(CaptureMessage::CaptureError(format!("Invalid {} signature: {}", name, err)));
+            return None;
+        }
+    };
+
+    match pattern.find_unique(code.bytes) {
+        Ok(found) => {
+            let offset = (code.offset + found) as isize;
+            submit_message(CaptureMessage::Info(format!("Found {} signature at offset {:#x}", name, offset)));
+            Some(offset)
+        },
+        Err(ScanError::NoMatch) => {
+            submit_message(CaptureMessage::CaptureError(format!("No match for the {} signature", name)));
+            None
+        },
+        Err(ScanError::Ambiguous(matches)) => {
+            submit_message(CaptureMessage::CaptureError(format!("The {} signature matched {} times", name, matches)));
+            None
+        }
+    }
+
+}
+
+/*
+    Only .text is scanned. The rest of SizeOfImage can include pages that were never committed or are
+    guarded, and reading those would take the game down with us. The PE headers are always mapped, so the
+    section table is read from there.
+*/
+unsafe fn module_code(hmodule: HMODULE) -> Option<ModuleCode> {
+
+    let mut info: MODULEINFO = mem::zeroed();
+    GetModuleInformation(GetCurrentProcess(), hmodule, &mut info, mem::size_of::<MODULEINFO>() as u32).ok()?;
+
+    let base = info.lpBaseOfDll as *const u8;
+    let image_size = info.SizeOfImage as usize;
+    let read_u16 = |offset: usize| u16::from_le_bytes([*base.add(offset), *base.add(offset + 1)]) as usize;
+    let read_u32 = |offset: usize| u32::from_le_bytes([*base.add(offset), *base.add(offset + 1), *base.add(offset + 2), *base.add(offset + 3)]) as usize;
+
+    if read_u16(0) != 0x5A4D {
+        return None;
+    }
+
+    let nt_headers = read_u32(0x3C);
+    if nt_headers + 24 > PE_HEADER_LIMIT || read_u32(nt_headers) != 0x4550 {
+        return None;
+    }
+
+    let section_count  = read_u16(nt_headers + 6);
+    let optional_size  = read_u16(nt_headers + 20);
+    let sections_start = nt_headers + 24 + optional_size;
+    if sections_start + section_count * SECTION_HEADER_SIZE > PE_HEADER_LIMIT {
+        return None;
+    }
+
+    for index in 0..section_count {
+
+        let section = sections_start + index * SECTION_HEADER_SIZE;
+        let name = slice::from_raw_parts(base.add(section), 8);
+        if name != b".text\0\0\0" {
+            continue;
+        }
+
+        let virtual_size    = read_u32(section + 8);
+        let virtual_address = read_u32(section + 12);
+        if virtual_size == 0 || virtual_address + virtual_size > image_size {
+            return None;
+        }
+
+        return Some(ModuleCode {
+            bytes: slice::from_raw_parts(base.add(virtual_address), virtual_size),
+            offset: virtual_address
+        });
+
+    }
+
+    None
+
+}
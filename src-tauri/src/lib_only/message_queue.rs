@@ -0,0 +1,297 @@
+use std::collections::VecDeque;
+
+use crate::share::health::DropReport;
+use crate::share::session::{QueueOverflowPolicy, QueueSettings};
+use crate::share::CaptureMessage;
+use crate::swtor::SwtorChannel;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Priority {
+    Global,
+    Other,
+    /// Whispers, emotes and anything the app needs to stay in sync. Never dropped.
+    Protected
+}
+
+struct QueuedMessage {
+    priority: Priority,
+    json: String
+}
+
+/*
+    Outgoing messages waiting for the writer thread. Bounded so it can't grow forever while the app is
+    disconnected; what gets dropped is counted so the app can show the gap.
+*/
+pub struct MessageQueue {
+    messages: VecDeque<QueuedMessage>,
+    settings: QueueSettings,
+    dropped: DropReport,
+    unreported: DropReport
+}
+
+impl Priority {
+
+    fn of(message: &CaptureMessage) -> Priority {
+
+        match message {
+            CaptureMessage::Chat(chat) => match SwtorChannel::try_from(chat.channel) {
+                Ok(SwtorChannel::GLOBAL) | Ok(SwtorChannel::PVP) | Ok(SwtorChannel::TRADE) => Priority::Global,
+                Ok(SwtorChannel::EMOTE) | Ok(SwtorChannel::WHISPER) => Priority::Protected,
+                _ => Priority::Other
+            },
+            CaptureMessage::Info(_) | CaptureMessage::Heartbeat(_) => Priority::Other,
+            _ => Priority::Protected
+        }
+
+    }
+
+}
+
+impl MessageQueue {
+
+    pub fn new(settings: QueueSettings) -> MessageQueue {
+
+        MessageQueue {
+            messages: VecDeque::new(),
+            settings,
+            dropped: DropReport::default(),
+            unreported: DropReport::default()
+        }
+
+    }
+
+    pub fn configure(&mut self, settings: QueueSettings) {
+
+        self.settings = settings;
+
+    }
+
+    pub fn len(&self) -> usize {
+
+        self.messages.len()
+
+    }
+
+    pub fn push(&mut self, message: &CaptureMessage) {
+
+        let priority = Priority::of(message);
+
+        if self.messages.len() >= self.settings.capacity {
+
+            let evicted = match (self.settings.overflow_policy, priority) {
+                (QueueOverflowPolicy::DropIncoming, Priority::Global) | (QueueOverflowPolicy::DropIncoming, Priority::Other) => false,
+                _ => self.evict_oldest(Priority::Global) || self.evict_oldest(Priority::Other)
+            };
+
+            // Only protected messages are left, so the queue is allowed to grow past its capacity for them.
+            if !evicted && priority != Priority::Protected {
+                self.count_drop(priority);
+                return;
+            }
+
+        }
+
+        self.messages.push_back(QueuedMessage {
+            priority,
+            json: message.as_json_str()
+        });
+
+    }
+
+    pub fn drain(&mut self) -> Vec<String> {
+
+        self.messages
+            .drain(..)
+            .map(|message| message.json)
+            .collect()
+
+    }
+
+    pub fn total_dropped(&self) -> DropReport {
+
+        self.dropped.clone()
+
+    }
+
+    /// Drops since the last call, if there were any.
+    pub fn take_unreported_drops(&mut self) -> Option<DropReport> {
+
+        if self.unreported.total() == 0 {
+            return None;
+        }
+
+        Some(std::mem::take(&mut self.unreported))
+
+    }
+
+    fn evict_oldest(&mut self, priority: Priority) -> bool {
+
+        match self.messages.iter().position(|message| message.priority == priority) {
+            Some(idx) => {
+                self.messages.remove(idx);
+                self.count_drop(priority);
+                true
+            },
+            None => false
+        }
+
+    }
+
+    fn count_drop(&mut self, priority: Priority) {
+
+        match priority {
+            Priority::Global => {
+                self.dropped.global    += 1;
+                self.unreported.global += 1;
+            },
+            _ => {
+                self.dropped.other    += 1;
+                self.unreported.other += 1;
+            }
+        }
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use crate::share::raw_swtor_message::RawSwtorMessage;
+
+    fn chat(channel: SwtorChannel, text: &str) -> CaptureMessage {
+
+        CaptureMessage::Chat(RawSwtorMessage::new(channel as i32, "Kaelyn".to_string(), String::new(), text.to_string()))
+
+    }
+
+    fn queue(capacity: usize, overflow_policy: QueueOverflowPolicy) -> MessageQueue {
+
+        MessageQueue::new(QueueSettings { capacity, overflow_policy })
+
+    }
+
+    fn contents(messages: &[&CaptureMessage]) -> Vec<String> {
+
+        messages.iter().map(|message| message.as_json_str()).collect()
+
+    }
+
+    #[test]
+    fn keeps_everything_under_capacity() {
+
+        let mut queue = queue(3, QueueOverflowPolicy::DropOldest);
+        let (first, second) = (chat(SwtorChannel::GLOBAL, "1"), chat(SwtorChannel::SAY, "2"));
+        queue.push(&first);
+        queue.push(&second);
+
+        assert_eq!(queue.len(), 2);
+        assert!(queue.take_unreported_drops().is_none());
+        assert_eq!(queue.drain(), contents(&[&first, &second]));
+        assert_eq!(queue.len(), 0);
+
+    }
+
+    #[test]
+    fn drop_oldest_evicts_global_chat_first() {
+
+        let mut queue = queue(3, QueueOverflowPolicy::DropOldest);
+        let say    = chat(SwtorChannel::SAY, "say");
+        let global = chat(SwtorChannel::GLOBAL, "global");
+        let trade  = chat(SwtorChannel::TRADE, "trade");
+        let guild  = chat(SwtorChannel::GUILD, "guild");
+
+        for message in [&say, &global, &trade, &guild] {
+            queue.push(message);
+        }
+
+        assert_eq!(queue.drain(), contents(&[&say, &trade, &guild]));
+        let dropped = queue.total_dropped();
+        assert_eq!((dropped.global, dropped.other), (1, 0));
+
+    }
+
+    #[test]
+    fn drop_oldest_falls_back_to_other_chat() {
+
+        let mut queue = queue(2, QueueOverflowPolicy::DropOldest);
+        let say   = chat(SwtorChannel::SAY, "say");
+        let guild = chat(SwtorChannel::GUILD, "guild");
+        let yell  = chat(SwtorChannel::YELL, "yell");
+
+        for message in [&say, &guild, &yell] {
+            queue.push(message);
+        }
+
+        assert_eq!(queue.drain(), contents(&[&guild, &yell]));
+        let dropped = queue.total_dropped();
+        assert_eq!((dropped.global, dropped.other), (0, 1));
+
+    }
+
+    #[test]
+    fn drop_incoming_discards_the_new_message() {
+
+        let mut queue = queue(2, QueueOverflowPolicy::DropIncoming);
+        let global = chat(SwtorChannel::GLOBAL, "global");
+        let say    = chat(SwtorChannel::SAY, "say");
+
+        queue.push(&global);
+        queue.push(&say);
+        queue.push(&chat(SwtorChannel::TRADE, "trade"));
+        queue.push(&chat(SwtorChannel::GUILD, "guild"));
+
+        assert_eq!(queue.drain(), contents(&[&global, &say]));
+        let dropped = queue.total_dropped();
+        assert_eq!((dropped.global, dropped.other), (1, 1));
+
+    }
+
+    #[test]
+    fn whispers_and_emotes_are_never_dropped() {
+
+        for policy in [QueueOverflowPolicy::DropOldest, QueueOverflowPolicy::DropIncoming] {
+
+            let mut queue = queue(2, policy);
+            let global  = chat(SwtorChannel::GLOBAL, "global");
+            let whisper = chat(SwtorChannel::WHISPER, "whisper");
+            let emote   = chat(SwtorChannel::EMOTE, "emote");
+            let friend  = CaptureMessage::FriendStatus { character: "Torven".to_string(), online: true };
+
+            for message in [&global, &whisper, &emote, &friend] {
+                queue.push(message);
+            }
+
+            // The global is evicted for the emote, and with nothing left to evict the queue grows for the rest.
+            assert_eq!(queue.len(), 3);
+            assert_eq!(queue.drain(), contents(&[&whisper, &emote, &friend]));
+            assert_eq!(queue.total_dropped().global, 1);
+
+        }
+
+    }
+
+    #[test]
+    fn drops_are_reported_once_but_totalled_for_good() {
+
+        let mut queue = queue(1, QueueOverflowPolicy::DropIncoming);
+        queue.push(&chat(SwtorChannel::SAY, "kept"));
+        queue.push(&chat(SwtorChannel::GLOBAL, "1"));
+        queue.push(&chat(SwtorChannel::GUILD, "2"));
+
+        let reported = queue.take_unreported_drops().unwrap();
+        assert_eq!((reported.global, reported.other), (1, 1));
+        assert!(queue.take_unreported_drops().is_none());
+
+        queue.push(&chat(SwtorChannel::GLOBAL, "3"));
+        let reported = queue.take_unreported_drops().unwrap();
+        assert_eq!((reported.global, reported.other), (1, 0));
+
+        let total = queue.total_dropped();
+        assert_eq!((total.global, total.other, total.total()), (2, 1, 3));
+
+    }
+
+}
@@ -0,0 +1,29 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::share::health::{CaptureStats, DropReport};
+
+static MESSAGES_CAPTURED: AtomicU64   = AtomicU64::new(0);
+static CONVERSION_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_captured() {
+
+    MESSAGES_CAPTURED.fetch_add(1, Ordering::Relaxed);
+
+}
+
+pub fn record_conversion_failure() {
+
+    CONVERSION_FAILURES.fetch_add(1, Ordering::Relaxed);
+
+}
+
+pub fn snapshot(queue_depth: usize, messages_dropped: DropReport) -> CaptureStats {
+
+    CaptureStats {
+        messages_captured: MESSAGES_CAPTURED.load(Ordering::Relaxed),
+        conversion_failures: CONVERSION_FAILURES.load(Ordering::Relaxed),
+        queue_depth,
+        messages_dropped
+    }
+
+}
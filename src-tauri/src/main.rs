@@ -18,6 +18,7 @@ mod utils;
 mod swtor;
 mod crash_reporter;
 mod config;
+mod game_signatures;
 mod logging;
 
 use crash_reporter::CrashReporter;
@@ -53,6 +54,9 @@ fn main() {
             window.set_always_on_top(settings.app.always_on_top)
                 .expect("error while setting always on top.");
 
+            capture_injector::auto_capture::start_auto_capture_watcher(window.clone());
+            swtor_hook::post::scheduler::start_scheduler(window);
+
             Ok(())
 
         })
@@ -60,6 +64,19 @@ fn main() {
             swtor_hook::start_swtor_hook,
             swtor_hook::is_hooked_in,
             swtor_hook::post::submit_actual_post,
+            swtor_hook::post::preview_post,
+            swtor_hook::post::post_queue::enqueue_post,
+            swtor_hook::post::post_queue::get_post_queue,
+            swtor_hook::post::post_queue::reorder_queued_post,
+            swtor_hook::post::post_queue::cancel_queued_post,
+            swtor_hook::post::post_queue::abort_current_post,
+            swtor_hook::post::scheduler::schedule_post,
+            swtor_hook::post::scheduler::schedule_post_after,
+            swtor_hook::post::scheduler::get_scheduled_posts,
+            swtor_hook::post::scheduler::cancel_scheduled_post,
+            swtor_hook::post::broadcast::broadcast_whisper,
+            swtor_hook::post::macros::validate_post_macro,
+            swtor_hook::post::macros::run_post_macro,
             open_link,
             dal::open_db_dir,
             dal::db::custom_emote::get_all_custom_emotes,
@@ -78,8 +95,26 @@ fn main() {
             dal::db::chat_log::get_todays_chat_log,
             dal::db::chat_log::datetags::get_all_date_tag_favourites,
             dal::db::chat_log::datetags::save_date_tag,
+            dal::db::friend_presence::get_friends_presence,
+            dal::db::capture_incident::get_capture_incidents,
+            dal::db::recipient_group::get_all_recipient_groups,
+            dal::db::recipient_group::save_recipient_group,
+            dal::db::recipient_group::delete_recipient_group,
+            dal::db::post_macro::get_all_post_macros,
+            dal::db::post_macro::save_post_macro,
+            dal::db::post_macro::delete_post_macro,
+            crash_reporter::submit_capture_incident_report,
             capture_injector::start_injecting_capture,
             capture_injector::stop_injecting_capture,
+            capture_injector::start_simulated_capture,
+            capture_injector::get_capture_health,
+            capture_injector::get_capture_state,
+            capture_injector::list_capture_recordings,
+            capture_injector::replay_capture_recording,
+            capture_injector::pause_capture,
+            capture_injector::resume_capture,
+            capture_injector::ping_capture,
+            capture_injector::set_capture_channel_filter,
             get_version
         ])
         .run(tauri::generate_context!());
@@ -150,6 +185,12 @@ fn setup_panic_hook() {
         };
 
         error!("Panic: {:?}", panic_info);
+
+        // The post worker catches its own panics and carries on, so there is nothing to stop or report.
+        if std::thread::current().name() == Some(swtor_hook::post::post_queue::POST_WORKER_THREAD) {
+            return;
+        }
+
         capture_injector::stop_injecting_capture();
         let response = ask(None::<&tauri::Window>, "ChaTOR Crash", "ChaTOR has crashed. Send a crash report?");
 
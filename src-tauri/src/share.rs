@@ -1,15 +1,33 @@
-
 use raw_swtor_message::RawSwtorMessage;
+use handshake::CaptureHello;
+use control::ControlAck;
+use health::{CaptureStats, DropReport};
+use auth::FrameError;
 use serde::{Deserialize, Serialize};
 
 pub mod raw_swtor_message;
+pub mod codec;
+pub mod handshake;
+pub mod auth;
+pub mod session;
+pub mod control;
+pub mod health;
+pub mod signature;
 
 #[derive(Deserialize, Serialize)]
 pub enum CaptureMessage {
+    Hello(CaptureHello),
     Info(String),
     CaptureError(String),
     Panic(String),
-    Chat(RawSwtorMessage)
+    Chat(RawSwtorMessage),
+    ControlAck(ControlAck),
+    Heartbeat(CaptureStats),
+    MessagesDropped(DropReport),
+    FriendStatus {
+        character: String,
+        online: bool
+    }
 }
 
 impl CaptureMessage {
@@ -18,4 +36,12 @@ impl CaptureMessage {
         serde_json::to_string(self).unwrap()
     }
 
-}
\ No newline at end of file
+    pub fn as_frame(&self, secret: &str) -> Vec<u8> {
+        auth::encode_signed(secret, self)
+    }
+
+    pub fn from_frame(secret: &str, frame: &[u8]) -> Result<CaptureMessage, FrameError> {
+        auth::decode_signed(secret, frame)
+    }
+
+}
@@ -0,0 +1,94 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::codec;
+
+/*
+    Every frame on the capture connection carries an HMAC-SHA256 of its payload, keyed with the per-session
+    secret the app hands to the DLL at injection time. Frames from anyone who doesn't know the secret are
+    rejected before they're deserialized.
+*/
+
+pub const MAC_SIZE: usize = 32;
+const BLOCK_SIZE: usize   = 64;
+
+#[derive(Debug)]
+pub enum FrameError {
+    Unauthenticated,
+    Malformed(String)
+}
+
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; MAC_SIZE] {
+
+    let mut block_key = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block_key[..MAC_SIZE].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block_key.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>());
+    inner.update(message);
+    let inner_hash = inner.finalize();
+
+    let mut outer = Sha256::new();
+    outer.update(block_key.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>());
+    outer.update(inner_hash);
+
+    let mut mac = [0u8; MAC_SIZE];
+    mac.copy_from_slice(&outer.finalize());
+    mac
+
+}
+
+pub fn sign(secret: &str, payload: &[u8]) -> Vec<u8> {
+
+    let mut signed = hmac_sha256(secret.as_bytes(), payload).to_vec();
+    signed.extend_from_slice(payload);
+    signed
+
+}
+
+pub fn verify<'a>(secret: &str, signed: &'a [u8]) -> Result<&'a [u8], FrameError> {
+
+    if signed.len() < MAC_SIZE {
+        return Err(FrameError::Unauthenticated);
+    }
+
+    let (mac, payload) = signed.split_at(MAC_SIZE);
+    let expected = hmac_sha256(secret.as_bytes(), payload);
+
+    // Compare every byte so the time taken doesn't leak how much of the MAC was right.
+    let difference = mac
+        .iter()
+        .zip(expected.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+
+    if difference != 0 {
+        return Err(FrameError::Unauthenticated);
+    }
+
+    Ok(payload)
+
+}
+
+pub fn encode_signed<T: Serialize>(secret: &str, value: &T) -> Vec<u8> {
+
+    encode_signed_json(secret, &serde_json::to_string(value).unwrap())
+
+}
+
+pub fn encode_signed_json(secret: &str, json: &str) -> Vec<u8> {
+
+    codec::encode_frame(&sign(secret, json.as_bytes()))
+
+}
+
+pub fn decode_signed<T: DeserializeOwned>(secret: &str, frame: &[u8]) -> Result<T, FrameError> {
+
+    let payload = verify(secret, frame)?;
+    serde_json::from_slice(payload).map_err(|e| FrameError::Malformed(e.to_string()))
+
+}
@@ -0,0 +1,213 @@
+/*
+    Wire format used between the injected capture DLL and the app.
+
+    Every frame is a 4 byte little-endian length followed by that many bytes of payload. Reads from a
+    TcpStream can end anywhere, so the decoder keeps whatever it has been given until a full frame is
+    available.
+*/
+
+pub const FRAME_HEADER_SIZE: usize = 4;
+pub const MAX_FRAME_SIZE: usize    = 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub enum CodecError {
+    FrameTooLarge(usize)
+}
+
+pub fn encode_frame(payload: &[u8]) -> Vec<u8> {
+
+    let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    frame
+
+}
+
+pub struct FrameDecoder {
+    buffer: Vec<u8>
+}
+
+impl FrameDecoder {
+
+    pub fn new() -> FrameDecoder {
+
+        FrameDecoder {
+            buffer: Vec::new()
+        }
+
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+
+        self.buffer.extend_from_slice(bytes);
+
+    }
+
+    /*
+        Returns the next complete frame, or None if more bytes are required. An error means the stream can no
+        longer be trusted to be aligned on a frame boundary, so it is returned again on every call and the
+        connection should be dropped.
+    */
+    pub fn next_frame(&mut self) -> Option<Result<Vec<u8>, CodecError>> {
+
+        if self.buffer.len() < FRAME_HEADER_SIZE {
+            return None;
+        }
+
+        let mut header = [0u8; FRAME_HEADER_SIZE];
+        header.copy_from_slice(&self.buffer[..FRAME_HEADER_SIZE]);
+        let length = u32::from_le_bytes(header) as usize;
+
+        if length > MAX_FRAME_SIZE {
+            return Some(Err(CodecError::FrameTooLarge(length)));
+        }
+
+        if self.buffer.len() < FRAME_HEADER_SIZE + length {
+            return None;
+        }
+
+        let frame: Vec<u8> = self.buffer
+            .drain(..FRAME_HEADER_SIZE + length)
+            .skip(FRAME_HEADER_SIZE)
+            .collect();
+
+        Some(Ok(frame))
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn drain(decoder: &mut FrameDecoder) -> Vec<Vec<u8>> {
+
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.next_frame() {
+            frames.push(frame.unwrap());
+        }
+        frames
+
+    }
+
+    #[test]
+    fn decodes_a_whole_frame() {
+
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&encode_frame(b"hello"));
+
+        assert_eq!(drain(&mut decoder), vec![b"hello".to_vec()]);
+
+    }
+
+    #[test]
+    fn decodes_an_empty_frame() {
+
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&encode_frame(b""));
+
+        assert_eq!(drain(&mut decoder), vec![Vec::<u8>::new()]);
+
+    }
+
+    #[test]
+    fn waits_for_a_split_header() {
+
+        let frame = encode_frame(b"hello");
+        let mut decoder = FrameDecoder::new();
+
+        decoder.push(&frame[..2]);
+        assert!(decoder.next_frame().is_none());
+
+        decoder.push(&frame[2..]);
+        assert_eq!(drain(&mut decoder), vec![b"hello".to_vec()]);
+
+    }
+
+    #[test]
+    fn waits_for_a_split_body() {
+
+        let frame = encode_frame(b"hello there");
+        let mut decoder = FrameDecoder::new();
+
+        decoder.push(&frame[..FRAME_HEADER_SIZE + 3]);
+        assert!(decoder.next_frame().is_none());
+
+        decoder.push(&frame[FRAME_HEADER_SIZE + 3..]);
+        assert_eq!(drain(&mut decoder), vec![b"hello there".to_vec()]);
+
+    }
+
+    #[test]
+    fn decodes_one_byte_at_a_time() {
+
+        let frame = encode_frame(b"slowly");
+        let mut decoder = FrameDecoder::new();
+
+        let mut frames = Vec::new();
+        for byte in frame.iter() {
+            decoder.push(&[*byte]);
+            frames.extend(drain(&mut decoder));
+        }
+
+        assert_eq!(frames, vec![b"slowly".to_vec()]);
+
+    }
+
+    #[test]
+    fn decodes_coalesced_frames() {
+
+        let mut stream = encode_frame(b"one");
+        stream.extend(encode_frame(b"two"));
+        stream.extend(encode_frame(b"three"));
+
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&stream);
+
+        assert_eq!(drain(&mut decoder), vec![b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]);
+
+    }
+
+    #[test]
+    fn keeps_a_trailing_partial_frame() {
+
+        let second = encode_frame(b"second");
+        let mut stream = encode_frame(b"first");
+        stream.extend_from_slice(&second[..5]);
+
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&stream);
+        assert_eq!(drain(&mut decoder), vec![b"first".to_vec()]);
+
+        decoder.push(&second[5..]);
+        assert_eq!(drain(&mut decoder), vec![b"second".to_vec()]);
+
+    }
+
+    #[test]
+    fn rejects_an_oversized_length_every_time() {
+
+        let length = MAX_FRAME_SIZE + 1;
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&(length as u32).to_le_bytes());
+        decoder.push(b"garbage that follows");
+
+        assert_eq!(decoder.next_frame(), Some(Err(CodecError::FrameTooLarge(length))));
+        assert_eq!(decoder.next_frame(), Some(Err(CodecError::FrameTooLarge(length))));
+
+    }
+
+    #[test]
+    fn accepts_the_maximum_length() {
+
+        let payload = vec![7u8; MAX_FRAME_SIZE];
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&encode_frame(&payload));
+
+        assert_eq!(drain(&mut decoder), vec![payload]);
+
+    }
+
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use super::auth::{self, FrameError};
+
+/// Commands the app sends to the injected DLL over the capture connection.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum ControlMessage {
+    Quit,
+    Pause,
+    Resume,
+    Ping(u64),
+    /// None captures every channel.
+    SetChannelFilter(Option<Vec<i32>>)
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ControlRequest {
+    pub id: u64,
+    pub message: ControlMessage
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum ControlReply {
+    Ack,
+    Pong(u64),
+    Rejected(String)
+}
+
+/// Sent back by the DLL for every ControlRequest it reads, carrying the same id.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ControlAck {
+    pub id: u64,
+    pub reply: ControlReply
+}
+
+impl ControlRequest {
+
+    pub fn as_frame(&self, secret: &str) -> Vec<u8> {
+        auth::encode_signed(secret, self)
+    }
+
+    pub fn from_frame(secret: &str, frame: &[u8]) -> Result<ControlRequest, FrameError> {
+        auth::decode_signed(secret, frame)
+    }
+
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever the framing or the shape of CaptureMessage changes in a way older builds can't read.
+pub const PROTOCOL_VERSION: u32 = 6;
+
+/// Offsets of the hooked functions relative to the base of swtor.exe.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
+pub struct HookOffsets {
+    pub chat: Option<isize>,
+    pub friends_list: Option<isize>
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CaptureHello {
+    pub crate_version: String,
+    pub protocol_version: u32,
+    pub offsets: HookOffsets
+}
+
+impl CaptureHello {
+
+    pub fn new(offsets: HookOffsets) -> CaptureHello {
+
+        CaptureHello {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            offsets
+        }
+
+    }
+
+    pub fn is_compatible(&self) -> bool {
+
+        self.protocol_version == PROTOCOL_VERSION && self.crate_version == env!("CARGO_PKG_VERSION")
+
+    }
+
+}
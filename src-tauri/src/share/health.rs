@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How often the DLL sends a Heartbeat while it is connected.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Running totals since the DLL was injected.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct CaptureStats {
+    pub messages_captured: u64,
+    pub conversion_failures: u64,
+    pub queue_depth: usize,
+    #[serde(default)]
+    pub messages_dropped: DropReport
+}
+
+/// Messages the DLL discarded because its queue was full.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct DropReport {
+    pub global: u64,
+    pub other: u64
+}
+
+impl DropReport {
+
+    pub fn total(&self) -> u64 {
+        self.global + self.other
+    }
+
+}
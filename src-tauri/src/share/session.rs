@@ -0,0 +1,113 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use em_libs::dal::em_dirs::EmDirs;
+use serde::{Deserialize, Serialize};
+
+use super::handshake::HookOffsets;
+use super::signature::HookSignatures;
+
+const SESSION_FILE: &str = "capture_session.json";
+const SECRET_SIZE: usize  = 32;
+
+/*
+    Rendezvous between the app and the injected DLL. The app writes this file into its data directory right
+    before injecting, and the DLL reads it back from inside the game process to learn which port the app
+    bound, which secret to sign frames with and where the functions to hook are for this game build.
+*/
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CaptureSession {
+    pub secret: String,
+    pub capture_port: u16,
+    #[serde(default)]
+    pub offsets: HookOffsets,
+    /// Used for any hook without an offset.
+    #[serde(default)]
+    pub signatures: HookSignatures,
+    #[serde(default)]
+    pub queue: QueueSettings
+}
+
+/// What the DLL does when its outgoing message queue is full, e.g. while the app is disconnected.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum QueueOverflowPolicy {
+    /// Evict the oldest global chat, then the oldest of anything else that isn't a whisper or emote.
+    DropOldest,
+    /// Discard the incoming message unless it is a whisper or emote, which still evicts as in DropOldest.
+    DropIncoming
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct QueueSettings {
+    pub capacity: usize,
+    pub overflow_policy: QueueOverflowPolicy
+}
+
+impl Default for QueueSettings {
+
+    fn default() -> QueueSettings {
+
+        QueueSettings {
+            capacity: 10_000,
+            overflow_policy: QueueOverflowPolicy::DropOldest
+        }
+
+    }
+
+}
+
+impl CaptureSession {
+
+    /// The secret is read straight from the OS's CSPRNG. Without one there is no session.
+    pub fn generate(capture_port: u16, offsets: HookOffsets, signatures: HookSignatures, queue: QueueSettings) -> Result<CaptureSession, &'static str> {
+
+        let mut secret = [0u8; SECRET_SIZE];
+        getrandom::getrandom(&mut secret)
+            .map_err(|_| "Error generating capture session secret")?;
+
+        Ok(CaptureSession {
+            secret: secret.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+            capture_port,
+            offsets,
+            signatures,
+            queue
+        })
+
+    }
+
+    pub fn read() -> Result<CaptureSession, &'static str> {
+
+        let contents = fs::read_to_string(session_path())
+            .map_err(|_| "Error reading capture session")?;
+
+        serde_json::from_str(&contents)
+            .map_err(|_| "Error parsing capture session")
+
+    }
+
+    pub fn write(&self) -> Result<(), &'static str> {
+
+        fs::write(session_path(), serde_json::to_string(self).unwrap())
+            .map_err(|_| "Error writing capture session")
+
+    }
+
+    /// Deletes the session file, so the secret isn't left on disk once nothing can use it.
+    pub fn remove() -> Result<(), &'static str> {
+
+        match fs::remove_file(session_path()) {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(_) => Err("Error removing capture session")
+        }
+
+    }
+
+}
+
+fn session_path() -> PathBuf {
+
+    PathBuf::from(EmDirs::new(env!("CARGO_PKG_NAME")).get_data_dir_path(SESSION_FILE))
+
+}
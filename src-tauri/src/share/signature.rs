@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+/*
+    IDA-style byte patterns, e.g. "48 89 5C 24 ?? 57 48 83 EC 20". Each token is one byte in hex, and "??"
+    (or "?") matches any byte. Used to find hook targets in a game build we have no offsets for.
+*/
+
+/// Patterns for the functions the DLL hooks. Each is expected to match the start of the function.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct HookSignatures {
+    pub chat: Option<String>,
+    pub friends_list: Option<String>
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScanError {
+    NoMatch,
+    /// A signature that matches more than once could just as well point at the wrong function.
+    Ambiguous(usize)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BytePattern {
+    bytes: Vec<Option<u8>>
+}
+
+impl BytePattern {
+
+    pub fn parse(pattern: &str) -> Result<BytePattern, String> {
+
+        let bytes = pattern
+            .split_whitespace()
+            .map(|token| match token {
+                "?" | "??" => Ok(None),
+                // from_str_radix alone would also take a sign, as in "+1".
+                _ if token.len() == 2 && token.chars().all(|c| c.is_ascii_hexdigit()) => u8::from_str_radix(token, 16)
+                    .map(Some)
+                    .map_err(|_| format!("Invalid byte '{}' in pattern", token)),
+                _ => Err(format!("Invalid byte '{}' in pattern", token))
+            })
+            .collect::<Result<Vec<Option<u8>>, String>>()?;
+
+        if bytes.is_empty() {
+            return Err("Pattern is empty".to_string());
+        }
+
+        if bytes.iter().all(|byte| byte.is_none()) {
+            return Err("Pattern must contain at least one concrete byte".to_string());
+        }
+
+        Ok(BytePattern { bytes })
+
+    }
+
+    /// Offset of the first match in `haystack`.
+    pub fn find_in(&self, haystack: &[u8]) -> Option<usize> {
+
+        if haystack.len() < self.bytes.len() {
+            return None;
+        }
+
+        haystack
+            .windows(self.bytes.len())
+            .position(|window| self.matches(window))
+
+    }
+
+    /// Number of matches in `haystack`. A usable signature matches exactly once.
+    pub fn count_in(&self, haystack: &[u8]) -> usize {
+
+        if haystack.len() < self.bytes.len() {
+            return 0;
+        }
+
+        haystack
+            .windows(self.bytes.len())
+            .filter(|window| self.matches(window))
+            .count()
+
+    }
+
+    /// Offset of the only match in `haystack`.
+    pub fn find_unique(&self, haystack: &[u8]) -> Result<usize, ScanError> {
+
+        match self.count_in(haystack) {
+            0 => Err(ScanError::NoMatch),
+            1 => self.find_in(haystack).ok_or(ScanError::NoMatch),
+            matches => Err(ScanError::Ambiguous(matches))
+        }
+
+    }
+
+    fn matches(&self, window: &[u8]) -> bool {
+
+        self.bytes
+            .iter()
+            .zip(window.iter())
+            .all(|(expected, actual)| expected.map_or(true, |expected| expected == *actual))
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const HAYSTACK: [u8; 12] = [0x48, 0x89, 0x5C, 0x24, 0x08, 0x57, 0x48, 0x83, 0xEC, 0x20, 0xC3, 0xCC];
+
+    fn pattern(pattern: &str) -> BytePattern {
+        BytePattern::parse(pattern).unwrap()
+    }
+
+    #[test]
+    fn matches_at_the_start() {
+
+        assert_eq!(pattern("48 89 5C").find_in(&HAYSTACK), Some(0));
+        assert_eq!(pattern("48 89 5C").find_unique(&HAYSTACK), Ok(0));
+
+    }
+
+    #[test]
+    fn matches_at_the_end() {
+
+        assert_eq!(pattern("20 C3 CC").find_in(&HAYSTACK), Some(9));
+        assert_eq!(pattern("20 C3 CC").find_unique(&HAYSTACK), Ok(9));
+
+    }
+
+    #[test]
+    fn matches_the_whole_haystack() {
+
+        assert_eq!(pattern("48 89 5C 24 08 57 48 83 EC 20 C3 CC").find_unique(&HAYSTACK), Ok(0));
+
+    }
+
+    #[test]
+    fn wildcards_match_any_byte() {
+
+        assert_eq!(pattern("5C 24 ?? 57").find_unique(&HAYSTACK), Ok(2));
+        assert_eq!(pattern("5C ? ?? 57").find_unique(&HAYSTACK), Ok(2));
+        assert_eq!(pattern("?? 20 C3 ??").find_unique(&HAYSTACK), Ok(8));
+
+    }
+
+    #[test]
+    fn lowercase_hex_is_accepted() {
+
+        assert_eq!(pattern("ec 20 c3").find_unique(&HAYSTACK), Ok(8));
+
+    }
+
+    #[test]
+    fn reports_no_match() {
+
+        assert_eq!(pattern("90 90").find_in(&HAYSTACK), None);
+        assert_eq!(pattern("90 90").find_unique(&HAYSTACK), Err(ScanError::NoMatch));
+        assert_eq!(pattern("20 C3 CC 00").find_unique(&HAYSTACK), Err(ScanError::NoMatch));
+
+    }
+
+    #[test]
+    fn pattern_longer_than_haystack_does_not_match() {
+
+        assert_eq!(pattern("48 89").find_unique(&[0x48]), Err(ScanError::NoMatch));
+        assert_eq!(pattern("48 89").count_in(&[]), 0);
+
+    }
+
+    #[test]
+    fn rejects_multiple_matches() {
+
+        assert_eq!(pattern("48").count_in(&HAYSTACK), 2);
+        assert_eq!(pattern("48").find_unique(&HAYSTACK), Err(ScanError::Ambiguous(2)));
+
+    }
+
+    #[test]
+    fn first_match_is_deterministic() {
+
+        assert_eq!(pattern("48 ??").find_in(&HAYSTACK), Some(0));
+
+    }
+
+    #[test]
+    fn counts_overlapping_matches() {
+
+        assert_eq!(pattern("AA AA").count_in(&[0xAA, 0xAA, 0xAA]), 2);
+
+    }
+
+    #[test]
+    fn rejects_bad_patterns() {
+
+        assert!(BytePattern::parse("").is_err());
+        assert!(BytePattern::parse("   ").is_err());
+        assert!(BytePattern::parse("?? ??").is_err());
+        assert!(BytePattern::parse("48 GG").is_err());
+        assert!(BytePattern::parse("488").is_err());
+        assert!(BytePattern::parse("4").is_err());
+        assert!(BytePattern::parse("48 ???").is_err());
+        assert!(BytePattern::parse("0x48").is_err());
+        assert!(BytePattern::parse("+1 48").is_err());
+
+    }
+
+}
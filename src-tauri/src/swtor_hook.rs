@@ -4,7 +4,7 @@ use std::path::Path;
 
 use sha2::{Sha256, Digest};
 
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
@@ -19,24 +19,25 @@ use windows::core::PWSTR;
 use windows::Win32::System::Threading::{OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_FORMAT, PROCESS_QUERY_INFORMATION};
 
 pub mod post;
-pub mod message_hash_container;
+pub mod echo_container;
 
 lazy_static! {
     static ref SWTOR_HWND: Arc<Mutex<Option<HWND>>> = Arc::new(Mutex::new(None));
     static ref SWTOR_PID: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(None));
+    /* Keyed by pid, as the game may have been restarted or patched since the checksum was taken. */
+    static ref PROCESS_CHECKSUM: Arc<Mutex<Option<(u32, Vec<u8>)>>> = Arc::new(Mutex::new(None));
 }
 
-static PROCESS_CHECKSUM: OnceLock<Vec<u8>> = OnceLock::new();
-
 
 
 unsafe fn set_process_checksum() {
 
-    if PROCESS_CHECKSUM.get().is_some() {
-        return;
-    }
-
     let pid = SWTOR_PID.lock().unwrap().clone().unwrap();
+    if let Some((checksum_pid, _)) = PROCESS_CHECKSUM.lock().unwrap().as_ref() {
+        if *checksum_pid == pid {
+            return;
+        }
+    }
 
     let handle = OpenProcess(PROCESS_QUERY_INFORMATION, false, pid);
     if handle.is_err() {
@@ -55,7 +56,7 @@ unsafe fn set_process_checksum() {
         let program_bytes = fs::read(path).unwrap();
         let mut hasher = Sha256::new();
         hasher.update(program_bytes);
-        PROCESS_CHECKSUM.set(hasher.finalize().as_slice().to_vec()).unwrap();
+        PROCESS_CHECKSUM.lock().unwrap().replace((pid, hasher.finalize().as_slice().to_vec()));
 
     }
 
@@ -140,10 +141,16 @@ pub fn get_hwnd() -> Option<HWND> {
 
 }
 
-pub fn checksum_match(checksum: &[u8; 32]) -> Result<bool, &'static str> {
+/// Upper case hex, the same form game_signatures.toml uses.
+pub fn get_checksum_hex() -> Result<String, &'static str> {
+
+    let pid = get_pid();
+    if let Some((checksum_pid, process_checksum)) = PROCESS_CHECKSUM.lock().unwrap().as_ref() {
+
+        if Some(*checksum_pid) == pid {
+            return Ok(process_checksum.iter().map(|b| format!("{:02X}", b)).collect::<String>());
+        }
 
-    if let Some(process_checksum) = PROCESS_CHECKSUM.get() {
-        return Ok(checksum.iter().eq(process_checksum.iter()));
     }
     return Err("PROCESS_CHECKSUM not yet initialized");
 
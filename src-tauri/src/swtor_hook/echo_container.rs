@@ -0,0 +1,304 @@
+use crate::dal::db::swtor_message::SwtorMessage;
+use crate::swtor::SwtorChannel;
+
+/// A chat message as captured while posting, reduced to what confirmation compares.
+pub struct Echo {
+    pub channel: i32,
+    pub from: String,
+    pub to: String,
+    pub text: String
+}
+
+impl From<&SwtorMessage> for Echo {
+
+    fn from(swtor_message: &SwtorMessage) -> Self {
+
+        Echo {
+            channel: swtor_message.channel,
+            from: swtor_message.from.clone(),
+            to: swtor_message.to.clone(),
+            text: normalize(&swtor_message.get_parsed_message())
+        }
+
+    }
+
+}
+
+/// What a typed part should come back as.
+pub struct ExpectedEcho {
+    /// None when the posting character isn't known, in which case any sender is accepted.
+    pub from: Option<String>,
+    /// Empty when the part goes to whatever channel the chat box is on.
+    pub channels: Vec<SwtorChannel>,
+    pub to: Option<String>,
+    pub text: String
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EchoMatch {
+    Confirmed,
+    WrongSender,
+    WrongChannel,
+    WrongRecipient
+}
+
+pub struct EchoContainer {
+    pub echoes: Vec<Echo>
+}
+
+impl EchoContainer {
+
+    pub fn new() -> EchoContainer {
+        EchoContainer {
+            echoes: Vec::new()
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.echoes.clear();
+    }
+
+    pub fn push(&mut self, echo: Echo) {
+        self.echoes.push(echo);
+    }
+
+    pub fn player_not_found(&self) -> bool {
+        self.echoes.iter().any(|echo| echo.channel == SwtorChannel::PlayerNotFound as i32)
+    }
+
+    /// The best match for `expected` among echoes with the same text. A confirmation wins over any mismatch.
+    pub fn find(&self, expected: &ExpectedEcho) -> Option<EchoMatch> {
+
+        let mut found: Option<EchoMatch> = None;
+        for echo in self.echoes.iter().filter(|echo| echo.text == expected.text) {
+
+            let echo_match = expected.compare(echo);
+            if echo_match == EchoMatch::Confirmed {
+                return Some(echo_match);
+            }
+            found = Some(echo_match);
+
+        }
+
+        found
+
+    }
+
+}
+
+impl ExpectedEcho {
+
+    fn compare(&self, echo: &Echo) -> EchoMatch {
+
+        if let Some(from) = self.from.as_ref() {
+            if !from.eq_ignore_ascii_case(&echo.from) {
+                return EchoMatch::WrongSender;
+            }
+        }
+
+        if !self.channels.is_empty() && !self.channels.iter().any(|channel| *channel as i32 == echo.channel) {
+            return EchoMatch::WrongChannel;
+        }
+
+        if let Some(to) = self.to.as_ref() {
+            if !to.eq_ignore_ascii_case(&echo.to) {
+                return EchoMatch::WrongRecipient;
+            }
+        }
+
+        EchoMatch::Confirmed
+
+    }
+
+}
+
+/// The channels a command posts to. Empty for plain text and commands we don't know.
+pub fn expected_channels(command: Option<&str>) -> Vec<SwtorChannel> {
+
+    let command = match command.and_then(|command| command.split_whitespace().next()) {
+        Some(command) => command.trim_start_matches("/").to_lowercase(),
+        None => return Vec::new()
+    };
+
+    match command.as_str() {
+        "e" | "emote" | "me" => vec![SwtorChannel::EMOTE],
+        "s" | "say" => vec![SwtorChannel::SAY],
+        "y" | "yell" => vec![SwtorChannel::YELL],
+        "w" | "whisper" => vec![SwtorChannel::WHISPER],
+        "p" | "party" | "group" => vec![SwtorChannel::GROUP],
+        "ops" | "op" => vec![SwtorChannel::OP, SwtorChannel::OPSAnnouncement],
+        "g" | "guild" => vec![SwtorChannel::GUILD],
+        "o" | "officer" => vec![SwtorChannel::GuildOfficer],
+        "gen" | "general" => vec![SwtorChannel::GLOBAL],
+        "pvp" => vec![SwtorChannel::PVP],
+        "trade" => vec![SwtorChannel::TRADE],
+        _ => Vec::new()
+    }
+
+}
+
+/// The character a `/w Name:` command whispers to.
+pub fn whisper_recipient(command: Option<&str>) -> Option<String> {
+
+    let command = command?.trim();
+    let recipient = command
+        .strip_prefix("/whisper")
+        .or_else(|| command.strip_prefix("/w"))
+        // Otherwise "/wave" would whisper "ave".
+        .filter(|rest| rest.starts_with(char::is_whitespace))?
+        .trim()
+        .trim_end_matches(":")
+        .trim();
+
+    match recipient.is_empty() {
+        true => None,
+        false => Some(recipient.to_string())
+    }
+
+}
+
+/// The game doesn't keep runs of whitespace, so neither side is compared with them.
+pub fn normalize(text: &str) -> String {
+
+    text
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn echo(channel: SwtorChannel, from: &str, to: &str, text: &str) -> Echo {
+
+        Echo {
+            channel: channel as i32,
+            from: from.to_string(),
+            to: to.to_string(),
+            text: normalize(text)
+        }
+
+    }
+
+    fn channels(command: &str) -> Vec<i32> {
+
+        expected_channels(Some(command))
+            .into_iter()
+            .map(|channel| channel as i32)
+            .collect()
+
+    }
+
+    fn find(echoes: Vec<Echo>, expected: &ExpectedEcho) -> Option<EchoMatch> {
+
+        EchoContainer { echoes }.find(expected)
+
+    }
+
+    #[test]
+    fn maps_commands_to_channels() {
+
+        assert_eq!(channels("/s"), vec![SwtorChannel::SAY as i32]);
+        assert_eq!(channels("/Say"), vec![SwtorChannel::SAY as i32]);
+        assert_eq!(channels("/e"), vec![SwtorChannel::EMOTE as i32]);
+        assert_eq!(channels("/me"), vec![SwtorChannel::EMOTE as i32]);
+        assert_eq!(channels("/w Kaelyn:"), vec![SwtorChannel::WHISPER as i32]);
+        assert_eq!(channels("/ops"), vec![SwtorChannel::OP as i32, SwtorChannel::OPSAnnouncement as i32]);
+        assert_eq!(channels("/g"), vec![SwtorChannel::GUILD as i32]);
+        assert_eq!(channels("/trade"), vec![SwtorChannel::TRADE as i32]);
+        assert!(channels("/roll").is_empty());
+        assert!(expected_channels(None).is_empty());
+
+    }
+
+    #[test]
+    fn finds_the_whisper_recipient() {
+
+        assert_eq!(whisper_recipient(Some("/w Kaelyn:")), Some("Kaelyn".to_string()));
+        assert_eq!(whisper_recipient(Some("/whisper  Syla Vorn :")), Some("Syla Vorn".to_string()));
+        assert_eq!(whisper_recipient(Some("/w :")), None);
+        assert_eq!(whisper_recipient(Some("/wave")), None);
+        assert_eq!(whisper_recipient(Some("/e")), None);
+        assert_eq!(whisper_recipient(None), None);
+
+    }
+
+    #[test]
+    fn confirms_say_and_emote() {
+
+        let say = ExpectedEcho {
+            from: Some("Kaelyn".to_string()),
+            channels: expected_channels(Some("/s")),
+            to: None,
+            text: normalize("Nobody  move.")
+        };
+
+        assert_eq!(find(vec![echo(SwtorChannel::SAY, "Kaelyn", "", "Nobody move.")], &say), Some(EchoMatch::Confirmed));
+        assert_eq!(find(vec![echo(SwtorChannel::SAY, "Torven", "", "Nobody move.")], &say), Some(EchoMatch::WrongSender));
+        assert_eq!(find(vec![echo(SwtorChannel::YELL, "Kaelyn", "", "Nobody move.")], &say), Some(EchoMatch::WrongChannel));
+        assert_eq!(find(vec![echo(SwtorChannel::SAY, "Kaelyn", "", "Everybody move.")], &say), None);
+
+        let emote = ExpectedEcho {
+            from: Some("Kaelyn".to_string()),
+            channels: expected_channels(Some("/e")),
+            to: None,
+            text: normalize("waves.")
+        };
+
+        assert_eq!(find(vec![echo(SwtorChannel::EMOTE, "kaelyn", "", "waves.")], &emote), Some(EchoMatch::Confirmed));
+
+    }
+
+    #[test]
+    fn confirms_whispers_to_the_recipient_whatever_the_case() {
+
+        let command = Some("/w syla vorn:");
+        let whisper = ExpectedEcho {
+            from: Some("Kaelyn".to_string()),
+            channels: expected_channels(command),
+            to: whisper_recipient(command),
+            text: normalize("See you there")
+        };
+
+        assert_eq!(find(vec![echo(SwtorChannel::WHISPER, "Kaelyn", "Syla Vorn", "See you there")], &whisper), Some(EchoMatch::Confirmed));
+        assert_eq!(find(vec![echo(SwtorChannel::WHISPER, "Kaelyn", "Jex", "See you there")], &whisper), Some(EchoMatch::WrongRecipient));
+
+    }
+
+    #[test]
+    fn confirmation_wins_over_mismatches() {
+
+        let guild = ExpectedEcho {
+            from: None,
+            channels: expected_channels(Some("/g")),
+            to: None,
+            text: normalize("Hi all")
+        };
+
+        let echoes = vec![
+            echo(SwtorChannel::GLOBAL, "Torven", "", "Hi all"),
+            echo(SwtorChannel::GUILD, "Torven", "", "Hi all")
+        ];
+
+        assert_eq!(find(echoes, &guild), Some(EchoMatch::Confirmed));
+
+    }
+
+    #[test]
+    fn any_channel_is_accepted_for_plain_text() {
+
+        let plain = ExpectedEcho {
+            from: Some("Kaelyn".to_string()),
+            channels: expected_channels(None),
+            to: None,
+            text: normalize("Hello")
+        };
+
+        assert_eq!(find(vec![echo(SwtorChannel::TRADE, "Kaelyn", "", "Hello")], &plain), Some(EchoMatch::Confirmed));
+
+    }
+
+}
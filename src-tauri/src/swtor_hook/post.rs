@@ -1,116 +1,113 @@
 
+use serde::Serialize;
 use tokio::task;
-use crate::swtor::SwtorChannel;
-use crate::utils::StringUtils;
+use tracing::warn;
 
-use crate::dal::db::user_character_messages::{CommandMessage, UserCharacterMessages};
+use crate::dal::db::settings;
+use crate::dal::db::swtor_message::SwtorMessage;
+use crate::dal::db::user_character_messages::UserCharacterMessages;
 
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 
-use crate::swtor_hook;
-use crate::swtor_hook::message_hash_container::MessageHashContainer;
+use crate::swtor_hook::echo_container::{self, Echo, EchoContainer, EchoMatch, ExpectedEcho};
 
-use windows::Win32::Foundation::{LPARAM, WPARAM};
-use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, SendMessageW, WM_CHAR, WM_KEYDOWN, WM_KEYUP};
+pub mod game_input;
+pub mod win32_input;
+pub mod recording_input;
+pub mod post_queue;
+pub mod scheduler;
+pub mod rate_limiter;
+pub mod sanitizer;
+pub mod broadcast;
+pub mod macros;
+
+use self::game_input::{GameInput, Key};
+use self::win32_input::Win32Input;
+use self::rate_limiter::OutboundChannel;
+use self::recording_input::RecordingInput;
+use self::sanitizer::Replacement;
 
 lazy_static! {
     static ref WRITING: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
-    static ref MESSAGE_HASH_CONTAINER: Arc<Mutex<MessageHashContainer>> = Arc::new(Mutex::new(MessageHashContainer::new()));
-}
-
-const ENTER_KEY: usize     = 0x0D;
-const BACKSPACE_KEY: usize = 0x08;
-const SHIFT_KEY: usize     = 0x10;
-
-pub fn push_incoming_message_hash(channel: SwtorChannel, hash: u64) {
-    MESSAGE_HASH_CONTAINER.lock().unwrap().push(channel, hash);
+    static ref ECHO_CONTAINER: Arc<Mutex<EchoContainer>> = Arc::new(Mutex::new(EchoContainer::new()));
 }
 
-fn post_message(msg_type: u32, wparam: usize, millis: u64) {
-
-    let wparam = WPARAM(wparam);
-
-    let hwnd = swtor_hook::get_hwnd();
-    if let Some(hwnd) = hwnd {
-
-        unsafe {
-            let _ = PostMessageW(hwnd, msg_type, wparam, LPARAM(0));
-        }
-        if millis > 0 {
-            thread::sleep(Duration::from_millis(millis));
-        }
-
-    }
+pub const PLAYER_NOT_FOUND: &str = "Player not found";
 
+pub fn push_incoming_message(swtor_message: &SwtorMessage) {
+    ECHO_CONTAINER.lock().unwrap().push(Echo::from(swtor_message));
 }
 
-fn send_message(msg_type: u32, wparam: usize, millis: u64) {
-
-    let wparam = WPARAM(wparam);
-
-    let hwnd = swtor_hook::get_hwnd();
-    if let Some(hwnd) = hwnd {
-
-        unsafe {
-            let _ = SendMessageW(hwnd, msg_type, wparam, LPARAM(0));
-        }
-        if millis > 0 {
-            thread::sleep(Duration::from_millis(millis));
-        }
-
-    }
-
-}
-
-fn prep_game_for_input() {
+fn prep_game_for_input(input: &mut dyn GameInput) {
 
     for _ in 0..64 {
-        send_message(WM_KEYDOWN, BACKSPACE_KEY, 2);
+        input.key_down_blocking(Key::Backspace);
+        input.wait(2);
     }
 
-    post_message(WM_KEYDOWN, SHIFT_KEY, 0);
-    post_message(WM_KEYDOWN, ENTER_KEY, 50);
+    input.key_down(Key::Shift);
+    input.key_down(Key::Enter);
+    input.wait(50);
 
-    post_message(WM_KEYUP, ENTER_KEY, 0);
-    post_message(WM_KEYUP, SHIFT_KEY, 50);
+    input.key_up(Key::Enter);
+    input.key_up(Key::Shift);
+    input.wait(50);
 
 }
 
-fn attempt_post_submission(message: &str) {
+fn attempt_post_submission(input: &mut dyn GameInput, message: &str) {
 
-    post_message(WM_KEYDOWN, ENTER_KEY, 250);
+    input.key_down(Key::Enter);
+    input.wait(250);
 
     for c in message.chars() {
 
-        post_message(WM_CHAR, c as usize, 10);
+        input.char(c);
+        input.wait(10);
 
     }
 
-    post_message(WM_KEYDOWN, ENTER_KEY, 20);
+    input.key_down(Key::Enter);
+    input.wait(20);
 
 }
 
-fn attempt_post_submission_with_retry(command_message: &CommandMessage) -> Result<(), &'static str> {
+/*
+    Retries until the part is echoed back by the posting character on the channel it was meant for. An echo
+    with the same text that doesn't match otherwise is most likely someone else saying the same line, or the
+    chat box having been on another channel, so it's noted but not taken as confirmation. Every attempt counts
+    toward `channel`'s rate limit, as the game sees each one whether or not it was echoed.
+*/
+fn attempt_post_submission_with_retry(input: &mut dyn GameInput, message: &str, channel: OutboundChannel, expected: &ExpectedEcho) -> Result<(), &'static str> {
 
-    let message_hash_cont = Arc::clone(&MESSAGE_HASH_CONTAINER);
-    let c_message      = command_message.concat();
-    let message_hash   = command_message.message.as_u64_hash();
+    let echo_cont = Arc::clone(&ECHO_CONTAINER);
+    let mut mismatch: Option<EchoMatch> = None;
 
     let mut retries = 0;
     while retries < 3 {
 
-        attempt_post_submission(&c_message);
+        attempt_post_submission(input, message);
+        rate_limiter::record(channel);
 
         for _ in 0..4 {
 
-            thread::sleep(Duration::from_millis(500));
-            if message_hash_cont.lock().unwrap().message_hashes.contains(&message_hash) {
-                return Ok(());
-            } else if  message_hash_cont.lock().unwrap().channels.contains(&SwtorChannel::PlayerNotFound) {
-                return Err("Player not found");
+            input.wait(500);
+            let echoes = echo_cont.lock().unwrap();
+
+            match echoes.find(expected) {
+                Some(EchoMatch::Confirmed) => return Ok(()),
+                Some(echo_match) if mismatch != Some(echo_match) => {
+                    warn!("Post part \"{}\" was echoed but did not match: {:?}", expected.text, echo_match);
+                    mismatch = Some(echo_match);
+                },
+                _ => {}
+            }
+
+            if echoes.player_not_found() {
+                return Err(PLAYER_NOT_FOUND);
             }
 
         }
@@ -119,47 +116,110 @@ fn attempt_post_submission_with_retry(command_message: &CommandMessage) -> Resul
 
     }
 
-    Err("Failed to post message")
+    match mismatch {
+        Some(EchoMatch::WrongSender) => Err("Message was echoed, but from another character"),
+        Some(EchoMatch::WrongChannel) => Err("Message was echoed, but on another channel"),
+        Some(EchoMatch::WrongRecipient) => Err("Message was echoed, but whispered to someone else"),
+        _ => Err("Failed to post message")
+    }
+
+}
 
+/// One line typed into chat. `confirm` is set when it should be retried until it's echoed back in chat.
+pub struct PostPart {
+    pub text: String,
+    pub channel: OutboundChannel,
+    pub confirm: Option<ExpectedEcho>,
+    /// Held back this long before typing, on top of any rate limit. Set by macro waits.
+    pub wait_before_ms: u64
 }
 
-fn retry_logic(character_message: UserCharacterMessages) -> Result<(), &'static str> {
+fn split_into_parts(retry: bool, character_message: &UserCharacterMessages) -> Result<Vec<PostPart>, &'static str> {
+
+    if !retry {
+
+        return Ok(character_message.messages
+            .iter()
+            .map(|message| PostPart {
+                text: message.clone(),
+                channel: OutboundChannel::of(message),
+                confirm: None,
+                wait_before_ms: 0
+            })
+            .collect());
+
+    }
 
     let command_messages = character_message.get_all_command_message_splits()?;
-    for command_message in command_messages {        
+    let character_name = character_message.get_character_name();
+    if character_name.is_none() {
+        warn!("Posting character is unknown, echoes will be confirmed by channel and text only");
+    }
+
+    Ok(command_messages
+        .iter()
+        .map(|command_message| PostPart {
+            text: command_message.concat(),
+            channel: OutboundChannel::of(&command_message.concat()),
+            confirm: match command_message.is_command_only() || !command_message.should_retry() {
+                true => None,
+                false => Some(ExpectedEcho {
+                    from: character_name.clone(),
+                    channels: echo_container::expected_channels(command_message.command.as_deref()),
+                    to: echo_container::whisper_recipient(command_message.command.as_deref()),
+                    text: echo_container::normalize(&command_message.message)
+                })
+            },
+            wait_before_ms: 0
+        })
+        .collect())
+
+}
+
+/// Sanitizes every message in place, returning what was replaced across all of them.
+fn sanitize_messages(character_message: &mut UserCharacterMessages) -> Vec<Replacement> {
+
+    let settings = settings::get_settings().chat.sanitizer;
+    let mut replacements: Vec<Replacement> = Vec::new();
+
+    for message in character_message.messages.iter_mut() {
 
-        if command_message.is_command_only() || !command_message.should_retry() {
-            attempt_post_submission(&command_message.concat());
-        } else {
-            attempt_post_submission_with_retry(&command_message)?;
+        let sanitized = sanitizer::sanitize(message, &settings);
+        *message = sanitized.text;
+
+        for replacement in sanitized.replacements {
+            if !replacements.contains(&replacement) {
+                replacements.push(replacement);
+            }
         }
 
-        thread::sleep(Duration::from_millis(250));
-        
     }
 
-    Ok(())
+    replacements
 
 }
 
-fn non_retry_logic(character_message: UserCharacterMessages) -> Result<(), &'static str> {
+fn post_part(input: &mut dyn GameInput, part: &PostPart) -> Result<(), &'static str> {
 
-    for message in character_message.messages {
-        attempt_post_submission(&message);
-        thread::sleep(Duration::from_millis(250));
+    match part.confirm.as_ref() {
+        Some(expected) => attempt_post_submission_with_retry(input, &part.text, part.channel, expected),
+        None => {
+            attempt_post_submission(input, &part.text);
+            rate_limiter::record(part.channel);
+            Ok(())
+        }
     }
 
-    Ok(())
-
 }
 
 fn block_window_focus_thread(window: tauri::Window) {
 
     thread::spawn(move || {
 
+        let input = Win32Input::new();
         while WRITING.load(Ordering::Relaxed) {
 
-            if swtor_hook::window_in_focus() {
+            if input.in_focus() {
 
                 match window.set_focus() {
                     Ok(_) => {},
@@ -175,37 +235,55 @@ fn block_window_focus_thread(window: tauri::Window) {
 
 }
 
-#[tauri::command]
-pub async fn submit_actual_post(window: tauri::Window, retry: bool, mut character_message: UserCharacterMessages) -> Result<(), &'static str> {
-
-    if WRITING.load(Ordering::Relaxed) {
-        return Err("Already writing");
-    }
+#[derive(Serialize)]
+pub struct PreviewPart {
+    pub typed: String,
+    pub channel: OutboundChannel
+}
 
-    WRITING.store(true, Ordering::Relaxed);
+#[derive(Serialize)]
+pub struct PostPreview {
+    pub parts: Vec<PreviewPart>,
+    pub replacements: Vec<Replacement>
+}
 
-    block_window_focus_thread(window);
-    let result = task::spawn_blocking(move || {
+/// What posting `character_message` would type, worked out against RecordingInput instead of the game.
+#[tauri::command]
+pub fn preview_post(retry: bool, mut character_message: UserCharacterMessages) -> Result<PostPreview, &'static str> {
 
-        character_message.prepare_messages();
-        character_message.store();
+    character_message.prepare_messages();
+    let replacements = sanitize_messages(&mut character_message);
+    character_message.fit_to_game_limits(&settings::get_settings().chat.continuation_marker)?;
+    let parts = split_into_parts(retry, &character_message)?;
 
-        let message_hash_cont   = Arc::clone(&MESSAGE_HASH_CONTAINER);
-        message_hash_cont.lock().unwrap().clear();
+    let mut input = RecordingInput::new();
+    for part in parts.iter() {
+        attempt_post_submission(&mut input, &part.text);
+    }
 
-        prep_game_for_input();
+    let parts = parts
+        .iter()
+        .zip(input.typed_lines())
+        .map(|(part, typed)| PreviewPart {
+            typed,
+            channel: part.channel
+        })
+        .collect();
 
-        if retry {
-            retry_logic(character_message)?;
-        } else {
-            non_retry_logic(character_message)?;
-        }
+    Ok(PostPreview { parts, replacements })
 
-        Ok(())
+}
 
-    }).await.unwrap();
+/*
+    Kept for the existing UI: queues the post like enqueue_post, then waits until it has been posted, failed
+    or was cancelled.
+*/
+#[tauri::command]
+pub async fn submit_actual_post(window: tauri::Window, retry: bool, character_message: UserCharacterMessages) -> Result<(), &'static str> {
 
-    WRITING.store(false, Ordering::Relaxed);
-    result
+    let (_, finished) = post_queue::enqueue(window, retry, character_message);
+    task::spawn_blocking(move || {
+        finished.recv().unwrap_or(Err("Post queue stopped"))
+    }).await.unwrap()
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,128 @@
+/*
+    The game only takes one recipient per whisper, so a broadcast is queued as one post per recipient. Each
+    goes through the post queue on its own and is confirmed by its echo, so a recipient who isn't online is
+    reported without holding up the rest.
+*/
+
+use serde::Serialize;
+use tokio::task;
+use tracing::{info, warn};
+
+use crate::dal::db::recipient_group::RecipientGroup;
+use crate::dal::db::settings;
+use crate::dal::db::user_character_messages::{MessageType, UserCharacterMessages};
+
+use super::{post_queue, PLAYER_NOT_FOUND};
+
+/// Emitted as broadcast_result as each recipient's whisper finishes, and returned for all of them at the end.
+#[derive(Serialize, Clone)]
+pub struct BroadcastResult {
+    pub recipient: String,
+    pub post_id: u64,
+    pub player_not_found: bool,
+    pub error: Option<String>
+}
+
+/// Trims and de-duplicates the recipients, adding those of the group if one is given.
+pub fn expand_recipients(recipients: Vec<String>, recipient_group_id: Option<i32>) -> Result<Vec<String>, &'static str> {
+
+    let mut all = recipients;
+    if let Some(recipient_group_id) = recipient_group_id {
+        all.extend(RecipientGroup::get(recipient_group_id)?.recipients);
+    }
+
+    let mut expanded: Vec<String> = Vec::new();
+    for recipient in all.iter().map(|recipient| recipient.trim()) {
+
+        if recipient.is_empty() {
+            continue;
+        }
+
+        if recipient.contains(":") {
+            return Err("Recipient names cannot contain ':'");
+        }
+
+        if !expanded.iter().any(|existing| existing.eq_ignore_ascii_case(recipient)) {
+            expanded.push(recipient.to_string());
+        }
+
+    }
+
+    if expanded.is_empty() {
+        return Err("Broadcast has no recipients");
+    }
+
+    Ok(expanded)
+
+}
+
+/// The same messages as whispers to `recipient`.
+fn whisper_to(recipient: &str, character_id: Option<i32>, messages: &[String]) -> UserCharacterMessages {
+
+    UserCharacterMessages {
+        message_type: MessageType::ChatMessage,
+        character_id,
+        messages: messages
+            .iter()
+            .map(|message| format!("/w {}: {}", recipient, message))
+            .collect()
+    }
+
+}
+
+#[tauri::command]
+pub async fn broadcast_whisper(window: tauri::Window, character_message: UserCharacterMessages, recipients: Vec<String>, recipient_group_id: Option<i32>) -> Result<Vec<BroadcastResult>, &'static str> {
+
+    if !settings::get_settings().chat_log.retry_message_submission {
+        return Err("Broadcasting needs message retry enabled, so each whisper can be confirmed");
+    }
+
+    if character_message.messages.is_empty() {
+        return Err("No messages to submit");
+    }
+
+    if character_message.messages.iter().any(|message| message.trim_start().starts_with("/")) {
+        return Err("Broadcast messages cannot start with a command");
+    }
+
+    let recipients = expand_recipients(recipients, recipient_group_id)?;
+    info!("Broadcasting to {} recipients", recipients.len());
+
+    let queued: Vec<_> = recipients
+        .into_iter()
+        .map(|recipient| {
+
+            let whisper = whisper_to(&recipient, character_message.character_id, &character_message.messages);
+            let (post_id, finished) = post_queue::enqueue(window.clone(), true, whisper);
+            (recipient, post_id, finished)
+
+        })
+        .collect();
+
+    task::spawn_blocking(move || {
+
+        let mut results: Vec<BroadcastResult> = Vec::new();
+        for (recipient, post_id, finished) in queued {
+
+            let error = finished.recv().unwrap_or(Err("Post queue stopped")).err();
+            if let Some(err) = error {
+                warn!("Broadcast whisper to {} failed: {}", recipient, err);
+            }
+
+            let result = BroadcastResult {
+                recipient,
+                post_id,
+                player_not_found: error == Some(PLAYER_NOT_FOUND),
+                error: error.map(|err| err.to_string())
+            };
+
+            window.emit("broadcast_result", result.clone()).unwrap();
+            results.push(result);
+
+        }
+
+        results
+
+    }).await.map_err(|_| "Broadcast stopped")
+
+}
@@ -0,0 +1,42 @@
+/*
+    What the posting engine needs from the game window. Win32Input is the real thing; RecordingInput keeps
+    the calls so the exact key sequence for a post can be inspected without the game.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Enter,
+    Backspace,
+    Shift
+}
+
+impl Key {
+
+    pub fn virtual_key_code(&self) -> usize {
+
+        match self {
+            Key::Enter     => 0x0D,
+            Key::Backspace => 0x08,
+            Key::Shift     => 0x10
+        }
+
+    }
+
+}
+
+pub trait GameInput {
+
+    fn key_down(&mut self, key: Key);
+
+    /// Like key_down, but only returns once the game has handled the key.
+    fn key_down_blocking(&mut self, key: Key);
+
+    fn key_up(&mut self, key: Key);
+
+    fn char(&mut self, c: char);
+
+    fn wait(&mut self, millis: u64);
+
+    fn in_focus(&self) -> bool;
+
+}
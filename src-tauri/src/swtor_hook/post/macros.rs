@@ -0,0 +1,431 @@
+/*
+    Macros script a short sequence of posts as one unit, one step per line:
+
+        # Comments and blank lines are skipped
+        emote draws a blaster.
+        wait 3s
+        say Nobody move.
+        whisper Some Gm: Just roleplay, nothing to worry about.
+        roll 100
+        /yell Raw commands are posted as they are.
+
+    A macro runs as a single post in the post queue, so nothing else is typed between its steps, and its
+    waits are checked for aborts like any rate limit.
+*/
+
+use regex::Regex;
+use serde::Serialize;
+use tracing::info;
+
+use crate::dal::db::post_macro::PostMacro;
+use crate::dal::db::user_character_messages::{MessageType, UserCharacterMessages};
+
+use super::post_queue;
+
+/// Longest a single wait, or several in a row, may hold a macro up.
+const MAX_WAIT_MS: u64 = 10 * 60 * 1000;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "step", rename_all = "lowercase")]
+pub enum MacroStep {
+    Post { line: usize, text: String },
+    Wait { line: usize, millis: u64 },
+    Roll { line: usize, dice: Option<String> }
+}
+
+/// `line` is 1-based, and None for problems with the macro as a whole.
+#[derive(Serialize, Clone, Debug)]
+pub struct MacroError {
+    pub line: Option<usize>,
+    pub message: String
+}
+
+impl MacroError {
+
+    fn at(line: usize, message: &str) -> MacroError {
+
+        MacroError {
+            line: Some(line),
+            message: message.to_string()
+        }
+
+    }
+
+    fn whole(message: &str) -> MacroError {
+
+        MacroError {
+            line: None,
+            message: message.to_string()
+        }
+
+    }
+
+}
+
+/// The command each channel keyword posts with.
+fn channel_command(keyword: &str) -> Option<&'static str> {
+
+    match keyword {
+        "say" => Some("/s"),
+        "yell" => Some("/y"),
+        "emote" => Some("/e"),
+        "group" | "party" => Some("/p"),
+        "ops" => Some("/ops"),
+        "guild" => Some("/g"),
+        "officer" => Some("/o"),
+        "general" => Some("/gen"),
+        "trade" => Some("/trade"),
+        "pvp" => Some("/pvp"),
+        _ => None
+    }
+
+}
+
+fn parse_wait(argument: &str) -> Result<u64, &'static str> {
+
+    let wait_re = Regex::new(r"^(\d+(?:\.\d+)?)\s*(ms|s)?$").unwrap();
+    let captures = wait_re.captures(argument).ok_or("Wait must be a number of seconds, like 3s or 500ms")?;
+
+    let amount: f64 = captures[1].parse().map_err(|_| "Wait is not a number")?;
+    let millis = match captures.get(2).map(|unit| unit.as_str()) {
+        Some("ms") => amount,
+        _ => amount * 1000.0
+    };
+
+    if millis > MAX_WAIT_MS as f64 {
+        return Err("Wait is longer than 10 minutes");
+    }
+
+    Ok(millis as u64)
+
+}
+
+fn parse_line(line: usize, text: &str) -> Result<MacroStep, &'static str> {
+
+    if text.starts_with("/") {
+
+        let command_re = Regex::new(r"^/[a-zA-Z0-9]+").unwrap();
+        return match command_re.is_match(text) {
+            true => Ok(MacroStep::Post { line, text: text.to_string() }),
+            false => Err("Expected a command after /")
+        };
+
+    }
+
+    let (keyword, argument) = match text.split_once(char::is_whitespace) {
+        Some((keyword, argument)) => (keyword.to_lowercase(), argument.trim()),
+        None => (text.to_lowercase(), "")
+    };
+
+    match keyword.as_str() {
+        "wait" => Ok(MacroStep::Wait { line, millis: parse_wait(argument)? }),
+        "roll" => {
+
+            if argument.is_empty() {
+                return Ok(MacroStep::Roll { line, dice: None });
+            }
+
+            let dice_re = Regex::new(r"^\d+(d\d+)?$").unwrap();
+            match dice_re.is_match(argument) {
+                true => Ok(MacroStep::Roll { line, dice: Some(argument.to_string()) }),
+                false => Err("Roll takes a number, like 100, or dice, like 2d6")
+            }
+
+        },
+        "whisper" => {
+
+            match argument.split_once(":") {
+                Some((recipient, message)) if !recipient.trim().is_empty() && !message.trim().is_empty() => {
+                    Ok(MacroStep::Post { line, text: format!("/w {}: {}", recipient.trim(), message.trim()) })
+                },
+                _ => Err("Whisper needs a recipient and a message, like whisper Name: Hello")
+            }
+
+        },
+        keyword => {
+
+            let command = channel_command(keyword).ok_or("Unknown step, expected a channel, wait, roll or a /command")?;
+            if argument.is_empty() {
+                return Err("Nothing to post");
+            }
+            Ok(MacroStep::Post { line, text: format!("{} {}", command, argument) })
+
+        }
+    }
+
+}
+
+/// Parses every line, collecting all the errors rather than stopping at the first.
+pub fn parse(source: &str) -> Result<Vec<MacroStep>, Vec<MacroError>> {
+
+    let mut steps: Vec<MacroStep> = Vec::new();
+    let mut errors: Vec<MacroError> = Vec::new();
+
+    for (index, text) in source.lines().enumerate() {
+
+        let line = index + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with("#") {
+            continue;
+        }
+
+        let step = match parse_line(line, text) {
+            Ok(step) => step,
+            Err(err) => {
+                errors.push(MacroError::at(line, err));
+                continue;
+            }
+        };
+
+        // Catches what posting would reject, such as two whispers in one line, before anything is typed.
+        if let MacroStep::Post { text, .. } = &step {
+
+            let check = UserCharacterMessages {
+                message_type: MessageType::ChatMessage,
+                character_id: None,
+                messages: vec![text.clone()]
+            };
+
+            if let Err(err) = check.get_all_command_message_splits() {
+                errors.push(MacroError::at(line, err));
+                continue;
+            }
+
+        }
+
+        steps.push(step);
+
+    }
+
+    if errors.is_empty() {
+        validate_steps(&steps, &mut errors);
+    }
+
+    match errors.is_empty() {
+        true => Ok(steps),
+        false => Err(errors)
+    }
+
+}
+
+fn validate_steps(steps: &[MacroStep], errors: &mut Vec<MacroError>) {
+
+    if !steps.iter().any(|step| !matches!(step, MacroStep::Wait { .. })) {
+        errors.push(MacroError::whole("Macro has nothing to post"));
+        return;
+    }
+
+    let mut waited = 0;
+    for step in steps.iter() {
+
+        match step {
+            MacroStep::Wait { line, millis } => {
+
+                waited += millis;
+                if waited > MAX_WAIT_MS {
+                    errors.push(MacroError::at(*line, "Waits in a row add up to more than 10 minutes"));
+                    return;
+                }
+
+            },
+            _ => waited = 0
+        }
+
+    }
+
+    if let Some(MacroStep::Wait { line, .. }) = steps.last() {
+        errors.push(MacroError::at(*line, "Nothing is posted after this wait"));
+    }
+
+}
+
+/// The messages to post and how long to wait before each one.
+fn compile(steps: &[MacroStep]) -> (Vec<String>, Vec<u64>) {
+
+    let mut messages: Vec<String> = Vec::new();
+    let mut waits: Vec<u64> = Vec::new();
+
+    let mut pending_wait = 0;
+    for step in steps.iter() {
+
+        let message = match step {
+            MacroStep::Wait { millis, .. } => {
+                pending_wait += millis;
+                continue;
+            },
+            MacroStep::Post { text, .. } => text.clone(),
+            MacroStep::Roll { dice: Some(dice), .. } => format!("/roll {}", dice),
+            MacroStep::Roll { dice: None, .. } => "/roll".to_string()
+        };
+
+        messages.push(message);
+        waits.push(pending_wait);
+        pending_wait = 0;
+
+    }
+
+    (messages, waits)
+
+}
+
+#[tauri::command]
+pub fn validate_post_macro(source: String) -> Result<Vec<MacroStep>, Vec<MacroError>> {
+
+    parse(&source)
+
+}
+
+/// Queues a saved macro as one post and returns its post id.
+#[tauri::command]
+pub fn run_post_macro(window: tauri::Window, post_macro_id: i32, retry: bool, character_id: Option<i32>) -> Result<u64, Vec<MacroError>> {
+
+    let post_macro = PostMacro::get(post_macro_id).map_err(|err| vec![MacroError::whole(err)])?;
+    let steps = parse(&post_macro.source)?;
+    let (messages, waits) = compile(&steps);
+
+    let character_message = UserCharacterMessages {
+        message_type: MessageType::ChatMessage,
+        character_id,
+        messages
+    };
+
+    let (post_id, _) = post_queue::enqueue_with_waits(window, retry, character_message, waits);
+    info!("Macro {} queued as post {}", post_macro.macro_name, post_id);
+    Ok(post_id)
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn post(line: usize, text: &str) -> MacroStep {
+
+        MacroStep::Post { line, text: text.to_string() }
+
+    }
+
+    /// The line and message of every error parsing `source` gives.
+    fn errors(source: &str) -> Vec<(Option<usize>, String)> {
+
+        parse(source)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| (error.line, error.message))
+            .collect()
+
+    }
+
+    #[test]
+    fn parses_each_step_kind() {
+
+        let source = "\
+            # Comments and blank lines are skipped\n\
+            \n\
+            emote draws a blaster.\n\
+            wait 3s\n\
+            Say Nobody move.\n\
+            whisper Some Gm : Just roleplay.\n\
+            roll 100\n\
+            /yell Raw commands are posted as they are.\n\
+            party Regroup.";
+
+        assert_eq!(parse(source).unwrap(), vec![
+            post(3, "/e draws a blaster."),
+            MacroStep::Wait { line: 4, millis: 3000 },
+            post(5, "/s Nobody move."),
+            post(6, "/w Some Gm: Just roleplay."),
+            MacroStep::Roll { line: 7, dice: Some("100".to_string()) },
+            post(8, "/yell Raw commands are posted as they are."),
+            post(9, "/p Regroup.")
+        ]);
+
+    }
+
+    #[test]
+    fn waits_take_seconds_or_milliseconds() {
+
+        assert_eq!(parse_wait("3"), Ok(3000));
+        assert_eq!(parse_wait("1.5s"), Ok(1500));
+        assert_eq!(parse_wait("250ms"), Ok(250));
+        assert_eq!(parse_wait("600s"), Ok(MAX_WAIT_MS));
+        assert!(parse_wait("601s").is_err());
+        assert!(parse_wait("3m").is_err());
+        assert!(parse_wait("").is_err());
+        assert!(parse_wait("-1s").is_err());
+
+    }
+
+    #[test]
+    fn waits_in_a_row_are_bounded_together() {
+
+        assert_eq!(errors("say Hi\nwait 400s\nwait 300s\nsay Bye"), vec![
+            (Some(3), "Waits in a row add up to more than 10 minutes".to_string())
+        ]);
+
+        assert!(parse("say Hi\nwait 400s\nsay Hi again\nwait 400s\nsay Bye").is_ok());
+
+    }
+
+    #[test]
+    fn a_macro_must_post_and_not_end_on_a_wait() {
+
+        assert_eq!(errors("wait 1s"), vec![(None, "Macro has nothing to post".to_string())]);
+        assert_eq!(errors("# Nothing here"), vec![(None, "Macro has nothing to post".to_string())]);
+        assert_eq!(errors("say Hi\nwait 1s"), vec![(Some(2), "Nothing is posted after this wait".to_string())]);
+
+    }
+
+    #[test]
+    fn rolls_take_a_number_or_dice() {
+
+        assert_eq!(parse("roll").unwrap(), vec![MacroStep::Roll { line: 1, dice: None }]);
+        assert_eq!(parse("roll 2d6").unwrap(), vec![MacroStep::Roll { line: 1, dice: Some("2d6".to_string()) }]);
+
+        for argument in ["d6", "2d", "two", "100 200"] {
+            assert_eq!(
+                errors(&format!("roll {}", argument)),
+                vec![(Some(1), "Roll takes a number, like 100, or dice, like 2d6".to_string())]
+            );
+        }
+
+    }
+
+    #[test]
+    fn rejects_unknown_and_incomplete_steps() {
+
+        assert_eq!(errors("dance wildly"), vec![
+            (Some(1), "Unknown step, expected a channel, wait, roll or a /command".to_string())
+        ]);
+        assert_eq!(errors("emote"), vec![(Some(1), "Nothing to post".to_string())]);
+        assert_eq!(errors("whisper Kaelyn"), vec![
+            (Some(1), "Whisper needs a recipient and a message, like whisper Name: Hello".to_string())
+        ]);
+        assert_eq!(errors("/ hi"), vec![(Some(1), "Expected a command after /".to_string())]);
+
+    }
+
+    #[test]
+    fn reports_every_error_with_its_line() {
+
+        let source = "say Fine\n\n# Comment\ndance\nwait soon\nsay Fine too\nroll 2x6\n/w Kaelyn: hi /w Torven: hi";
+        let lines: Vec<Option<usize>> = errors(source).into_iter().map(|(line, _)| line).collect();
+
+        assert_eq!(lines, vec![Some(4), Some(5), Some(7), Some(8)]);
+
+    }
+
+    #[test]
+    fn compiles_waits_onto_the_next_post() {
+
+        let steps = parse("wait 1s\nsay Hi\nwait 2s\nwait 500ms\nroll\nemote waves.").unwrap();
+
+        assert_eq!(compile(&steps), (
+            vec!["/s Hi".to_string(), "/roll".to_string(), "/e waves.".to_string()],
+            vec![1000, 2500, 0]
+        ));
+
+    }
+
+}
@@ -0,0 +1,426 @@
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::dal::db::settings;
+use crate::dal::db::user_character_messages::UserCharacterMessages;
+
+use super::game_input::GameInput;
+use super::rate_limiter::{self, OutboundChannel};
+use super::win32_input::Win32Input;
+use super::{block_window_focus_thread, post_part, prep_game_for_input, sanitize_messages, split_into_parts, ECHO_CONTAINER, WRITING};
+
+struct QueuedPost {
+    post_id: u64,
+    retry: bool,
+    character_message: UserCharacterMessages,
+    /// Milliseconds to wait before each message, by index. Missing entries don't wait.
+    waits: Vec<u64>,
+    finished: Sender<Result<(), &'static str>>
+}
+
+#[derive(Serialize, Clone)]
+pub struct QueuedPostSummary {
+    pub post_id: u64,
+    pub retry: bool,
+    pub messages: Vec<String>
+}
+
+/// Emitted as post_queue whenever it changes.
+#[derive(Serialize, Clone)]
+pub struct PostQueueSnapshot {
+    pub in_flight: Option<QueuedPostSummary>,
+    pub queued: Vec<QueuedPostSummary>
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum PartStatus {
+    /// Held back by a macro wait.
+    Waiting,
+    Typing,
+    /// Seen in chat, or simply typed for posts that aren't retried.
+    Confirmed,
+    Failed(String)
+}
+
+/// Emitted as post_rate_limited when a part has to wait before it can be typed.
+#[derive(Serialize, Clone)]
+pub struct PostRateLimited {
+    pub post_id: u64,
+    pub part: usize,
+    pub channel: OutboundChannel,
+    pub wait_ms: u64
+}
+
+/// Emitted as post_progress for every part of a post.
+#[derive(Serialize, Clone)]
+pub struct PostProgress {
+    pub post_id: u64,
+    pub part: usize,
+    pub parts: usize,
+    pub status: PartStatus
+}
+
+struct PostQueue {
+    queued: VecDeque<QueuedPost>,
+    in_flight: Option<QueuedPostSummary>,
+    window: Option<tauri::Window>
+}
+
+lazy_static! {
+    static ref POST_QUEUE: Arc<Mutex<PostQueue>> = Arc::new(Mutex::new(PostQueue {
+        queued: VecDeque::new(),
+        in_flight: None,
+        window: None
+    }));
+    static ref WORKER_STARTED: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+    static ref ABORT_IN_FLIGHT: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+}
+
+static NEXT_POST_ID: AtomicU64 = AtomicU64::new(1);
+
+const CANCELLED: &str = "Post cancelled";
+const POST_PANICKED: &str = "Post failed unexpectedly";
+const ABORT_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Queues a post and returns its id, along with a receiver for its result.
+pub fn enqueue(window: tauri::Window, retry: bool, character_message: UserCharacterMessages) -> (u64, Receiver<Result<(), &'static str>>) {
+
+    enqueue_with_waits(window, retry, character_message, Vec::new())
+
+}
+
+/// Like enqueue, but holds back each message for the matching entry of `waits` first. Used to run macros as one post.
+pub fn enqueue_with_waits(window: tauri::Window, retry: bool, character_message: UserCharacterMessages, waits: Vec<u64>) -> (u64, Receiver<Result<(), &'static str>>) {
+
+    let (finished, receiver) = mpsc::channel();
+    let post_id = NEXT_POST_ID.fetch_add(1, Ordering::Relaxed);
+
+    {
+        let mut queue = POST_QUEUE.lock().unwrap();
+        queue.window = Some(window.clone());
+        queue.queued.push_back(QueuedPost {
+            post_id,
+            retry,
+            character_message,
+            waits,
+            finished
+        });
+        queue.emit_snapshot();
+    }
+
+    if !WORKER_STARTED.swap(true, Ordering::Relaxed) {
+        start_post_worker(window);
+    }
+
+    (post_id, receiver)
+
+}
+
+pub fn snapshot() -> PostQueueSnapshot {
+
+    POST_QUEUE.lock().unwrap().snapshot()
+
+}
+
+/// Moves a queued post to `index`, clamped to the end of the queue.
+pub fn reorder(post_id: u64, index: usize) -> Result<(), &'static str> {
+
+    let mut queue = POST_QUEUE.lock().unwrap();
+    let position = queue.position(post_id).ok_or("Post is not queued")?;
+
+    let post  = queue.queued.remove(position).unwrap();
+    let index = index.min(queue.queued.len());
+    queue.queued.insert(index, post);
+    queue.emit_snapshot();
+    Ok(())
+
+}
+
+/// Cancels a queued post, or aborts it between parts if it is already being typed.
+pub fn cancel(post_id: u64) -> Result<(), &'static str> {
+
+    let mut queue = POST_QUEUE.lock().unwrap();
+
+    if queue.in_flight.as_ref().map(|post| post.post_id) == Some(post_id) {
+        ABORT_IN_FLIGHT.store(true, Ordering::Relaxed);
+        return Ok(());
+    }
+
+    let position = queue.position(post_id).ok_or("Post is not queued")?;
+    let post = queue.queued.remove(position).unwrap();
+    let _ = post.finished.send(Err(CANCELLED));
+
+    queue.emit_snapshot();
+    Ok(())
+
+}
+
+pub fn abort_in_flight() {
+
+    if POST_QUEUE.lock().unwrap().in_flight.is_some() {
+        ABORT_IN_FLIGHT.store(true, Ordering::Relaxed);
+    }
+
+}
+
+/// Named so the panic hook can tell a panic while posting, which the worker recovers from, from any other.
+pub const POST_WORKER_THREAD: &str = "post_worker";
+
+fn start_post_worker(window: tauri::Window) {
+
+    let worker = thread::Builder::new().name(POST_WORKER_THREAD.to_string());
+    worker.spawn(move || {
+
+        let mut input = Win32Input::new();
+        loop {
+
+            let next = {
+
+                let mut queue = POST_QUEUE.lock().unwrap();
+                let next = queue.queued.pop_front();
+                if let Some(post) = next.as_ref() {
+                    // Cleared before the post is visible as in flight, so a cancel from then on isn't lost.
+                    ABORT_IN_FLIGHT.store(false, Ordering::Relaxed);
+                    queue.in_flight = Some(post.summary());
+                    queue.emit_snapshot();
+                }
+                next
+
+            };
+
+            let post = match next {
+                Some(post) => post,
+                None => {
+                    thread::sleep(Duration::from_millis(100));
+                    continue;
+                }
+            };
+
+            WRITING.store(true, Ordering::Relaxed);
+            block_window_focus_thread(window.clone());
+
+            // A panic fails this post rather than the app, and the worker carries on with the rest of the queue.
+            let post_id = post.post_id;
+            let typed = panic::catch_unwind(AssertUnwindSafe(|| {
+                type_post(&window, &mut input, post.post_id, post.retry, post.character_message, &post.waits)
+            }));
+
+            let result = match typed {
+                Ok(result) => result,
+                Err(_) => {
+                    error!("Post {} panicked while it was being typed", post_id);
+                    let failed = PostProgress { post_id, part: 0, parts: 0, status: PartStatus::Failed(POST_PANICKED.to_string()) };
+                    if let Err(err) = window.emit("post_progress", failed) {
+                        error!("Error emitting post progress: {:?}", err);
+                    }
+                    Err(POST_PANICKED)
+                }
+            };
+
+            if let Err(err) = result {
+                warn!("Post {} was not completed: {}", post_id, err);
+            }
+
+            WRITING.store(false, Ordering::Relaxed);
+            let _ = post.finished.send(result);
+
+            let mut queue = POST_QUEUE.lock().unwrap();
+            queue.in_flight = None;
+            queue.emit_snapshot();
+
+        }
+
+    }).unwrap();
+
+}
+
+fn type_post(window: &tauri::Window, input: &mut dyn GameInput, post_id: u64, retry: bool, mut character_message: UserCharacterMessages, waits: &[u64]) -> Result<(), &'static str> {
+
+    let progress = |part: usize, parts: usize, status: PartStatus| {
+        if let Err(err) = window.emit("post_progress", PostProgress { post_id, part, parts, status }) {
+            error!("Error emitting post progress: {:?}", err);
+        }
+    };
+
+    character_message.prepare_messages();
+    let replacements = sanitize_messages(&mut character_message);
+    if !replacements.is_empty() {
+        info!("Post {} had {} replacements before typing", post_id, replacements.len());
+    }
+
+    // Sanitizing can change lengths, so this has to come after it.
+    let continuation_marker = settings::get_settings().chat.continuation_marker;
+    let origins = match character_message.fit_to_game_limits(&continuation_marker) {
+        Ok(origins) => origins,
+        Err(err) => {
+            progress(0, 0, PartStatus::Failed(err.to_string()));
+            return Err(err);
+        }
+    };
+    character_message.store();
+
+    ECHO_CONTAINER.lock().unwrap().clear();
+
+    let mut parts = match split_into_parts(retry, &character_message) {
+        Ok(parts) => parts,
+        Err(err) => {
+            progress(0, 0, PartStatus::Failed(err.to_string()));
+            return Err(err);
+        }
+    };
+
+    // A message split to fit only waits before its first part.
+    for (index, part) in parts.iter_mut().enumerate() {
+
+        let origin = origins[index];
+        if index == 0 || origins[index - 1] != origin {
+            part.wait_before_ms = waits.get(origin).copied().unwrap_or(0);
+        }
+
+    }
+
+    info!("Posting {} with {} parts", post_id, parts.len());
+    prep_game_for_input(input);
+
+    let rate_limits = settings::get_settings().chat.rate_limits;
+    for (index, part) in parts.iter().enumerate() {
+
+        if part.wait_before_ms > 0 {
+            progress(index, parts.len(), PartStatus::Waiting);
+        }
+        let waited_until = Instant::now() + Duration::from_millis(part.wait_before_ms);
+
+        let wait = rate_limiter::wait_for(part.channel, &rate_limits);
+        if !wait.is_zero() {
+            let rate_limited = PostRateLimited {
+                post_id,
+                part: index,
+                channel: part.channel,
+                wait_ms: wait.as_millis() as u64
+            };
+
+            if let Err(err) = window.emit("post_rate_limited", rate_limited) {
+                error!("Error emitting post rate limit: {:?}", err);
+            }
+        }
+
+        // Waits in short steps, so the post can still be aborted while it's held back.
+        loop {
+
+            // Only checked between parts, so a part is never left half typed in the chat box.
+            if ABORT_IN_FLIGHT.swap(false, Ordering::Relaxed) {
+                progress(index, parts.len(), PartStatus::Failed(CANCELLED.to_string()));
+                return Err(CANCELLED);
+            }
+
+            let wait = rate_limiter::wait_for(part.channel, &rate_limits)
+                .max(waited_until.saturating_duration_since(Instant::now()));
+            if wait.is_zero() {
+                break;
+            }
+            input.wait(wait.min(ABORT_CHECK_INTERVAL).as_millis() as u64);
+
+        }
+
+        progress(index, parts.len(), PartStatus::Typing);
+        match post_part(input, part) {
+            Ok(_) => progress(index, parts.len(), PartStatus::Confirmed),
+            Err(err) => {
+                progress(index, parts.len(), PartStatus::Failed(err.to_string()));
+                return Err(err);
+            }
+        }
+
+    }
+
+    Ok(())
+
+}
+
+impl QueuedPost {
+
+    fn summary(&self) -> QueuedPostSummary {
+
+        QueuedPostSummary {
+            post_id: self.post_id,
+            retry: self.retry,
+            messages: self.character_message.messages.clone()
+        }
+
+    }
+
+}
+
+impl PostQueue {
+
+    fn position(&self, post_id: u64) -> Option<usize> {
+
+        self.queued.iter().position(|post| post.post_id == post_id)
+
+    }
+
+    fn snapshot(&self) -> PostQueueSnapshot {
+
+        PostQueueSnapshot {
+            in_flight: self.in_flight.clone(),
+            queued: self.queued.iter().map(|post| post.summary()).collect()
+        }
+
+    }
+
+    fn emit_snapshot(&self) {
+
+        if let Some(window) = self.window.as_ref() {
+            if let Err(err) = window.emit("post_queue", self.snapshot()) {
+                error!("Error emitting post queue: {:?}", err);
+            }
+        }
+
+    }
+
+}
+
+#[tauri::command]
+pub fn enqueue_post(window: tauri::Window, retry: bool, character_message: UserCharacterMessages) -> u64 {
+
+    let (post_id, _) = enqueue(window, retry, character_message);
+    post_id
+
+}
+
+#[tauri::command]
+pub fn get_post_queue() -> PostQueueSnapshot {
+
+    snapshot()
+
+}
+
+#[tauri::command]
+pub fn reorder_queued_post(post_id: u64, index: usize) -> Result<(), &'static str> {
+
+    reorder(post_id, index)
+
+}
+
+#[tauri::command]
+pub fn cancel_queued_post(post_id: u64) -> Result<(), &'static str> {
+
+    cancel(post_id)
+
+}
+
+#[tauri::command]
+pub fn abort_current_post() {
+
+    abort_in_flight()
+
+}
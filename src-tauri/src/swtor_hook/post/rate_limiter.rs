@@ -0,0 +1,223 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::dal::db::settings::chat_settings::rate_limit::{ChannelRateLimit, RateLimitSettings};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// The kinds of channel the game throttles differently.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OutboundChannel {
+    /// Say, yell, emote, and plain text, which goes to whatever channel the chat box is on.
+    Local,
+    /// General, trade, PvP and numbered channels.
+    Public,
+    Whisper,
+    /// Group, ops and guild.
+    Group
+}
+
+impl OutboundChannel {
+
+    /// Picks the channel from the command a line starts with, if any.
+    pub fn of(text: &str) -> OutboundChannel {
+
+        if !text.starts_with("/") {
+            return OutboundChannel::Local;
+        }
+
+        let command = text
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches("/")
+            .to_lowercase();
+
+        if !command.is_empty() && command.chars().all(|c| c.is_ascii_digit()) {
+            return OutboundChannel::Public;
+        }
+
+        match command.as_str() {
+            "w" | "whisper" | "t" | "tell" | "r" | "reply" => OutboundChannel::Whisper,
+            "gen" | "general" | "trade" | "pvp" => OutboundChannel::Public,
+            "p" | "party" | "group" | "ops" | "op" | "g" | "guild" | "o" | "officer" => OutboundChannel::Group,
+            _ => OutboundChannel::Local
+        }
+
+    }
+
+    fn limit(&self, settings: &RateLimitSettings) -> ChannelRateLimit {
+
+        match self {
+            OutboundChannel::Local   => settings.local,
+            OutboundChannel::Public  => settings.public,
+            OutboundChannel::Whisper => settings.whisper,
+            OutboundChannel::Group   => settings.group
+        }
+
+    }
+
+}
+
+/// When parts were last posted to each channel, across posts.
+struct RateLimiter {
+    sent: HashMap<OutboundChannel, VecDeque<Instant>>
+}
+
+lazy_static! {
+    static ref RATE_LIMITER: Arc<Mutex<RateLimiter>> = Arc::new(Mutex::new(RateLimiter {
+        sent: HashMap::new()
+    }));
+}
+
+impl RateLimiter {
+
+    fn wait_for(&mut self, channel: OutboundChannel, settings: &RateLimitSettings, now: Instant) -> Duration {
+
+        let limit = channel.limit(settings);
+        let sent = self.sent.entry(channel).or_default();
+
+        while let Some(at) = sent.front() {
+
+            if now.duration_since(*at) < MINUTE {
+                break;
+            }
+            sent.pop_front();
+
+        }
+
+        let mut ready_at = now;
+        if let Some(last) = sent.back() {
+            ready_at = ready_at.max(*last + Duration::from_millis(limit.min_interval_ms));
+        }
+
+        if limit.max_per_minute > 0 && sent.len() >= limit.max_per_minute as usize {
+            // The oldest that has to fall out of the minute for there to be room again.
+            let oldest = sent[sent.len() - limit.max_per_minute as usize];
+            ready_at = ready_at.max(oldest + MINUTE);
+        }
+
+        ready_at.saturating_duration_since(now)
+
+    }
+
+    fn record(&mut self, channel: OutboundChannel, at: Instant) {
+
+        self.sent.entry(channel).or_default().push_back(at);
+
+    }
+
+}
+
+/// How long to hold off before posting to `channel`. Zero when it can go now.
+pub fn wait_for(channel: OutboundChannel, settings: &RateLimitSettings) -> Duration {
+
+    RATE_LIMITER.lock().unwrap().wait_for(channel, settings, Instant::now())
+
+}
+
+/// Counts a line typed into `channel`, whether or not it went through.
+pub fn record(channel: OutboundChannel) {
+
+    RATE_LIMITER.lock().unwrap().record(channel, Instant::now());
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use crate::dal::db::settings::chat_settings::rate_limit::default_local;
+
+    fn limiter() -> RateLimiter {
+
+        RateLimiter {
+            sent: HashMap::new()
+        }
+
+    }
+
+    fn settings(min_interval_ms: u64, max_per_minute: u32) -> RateLimitSettings {
+
+        let limit = ChannelRateLimit { min_interval_ms, max_per_minute };
+        RateLimitSettings {
+            local: default_local(),
+            public: limit,
+            whisper: limit,
+            group: limit
+        }
+
+    }
+
+    #[test]
+    fn classifies_channels_by_command() {
+
+        assert_eq!(OutboundChannel::of("Hello there"), OutboundChannel::Local);
+        assert_eq!(OutboundChannel::of("/e waves."), OutboundChannel::Local);
+        assert_eq!(OutboundChannel::of("/s Hi"), OutboundChannel::Local);
+        assert_eq!(OutboundChannel::of("/w Kaelyn: hi"), OutboundChannel::Whisper);
+        assert_eq!(OutboundChannel::of("/Tell Kaelyn: hi"), OutboundChannel::Whisper);
+        assert_eq!(OutboundChannel::of("/r hi"), OutboundChannel::Whisper);
+        assert_eq!(OutboundChannel::of("/gen LFG"), OutboundChannel::Public);
+        assert_eq!(OutboundChannel::of("/trade WTS"), OutboundChannel::Public);
+        assert_eq!(OutboundChannel::of("/3 hello"), OutboundChannel::Public);
+        assert_eq!(OutboundChannel::of("/ops Pull"), OutboundChannel::Group);
+        assert_eq!(OutboundChannel::of("/g Hi all"), OutboundChannel::Group);
+        assert_eq!(OutboundChannel::of("/"), OutboundChannel::Local);
+
+    }
+
+    #[test]
+    fn nothing_sent_means_no_wait() {
+
+        let now = Instant::now();
+        assert_eq!(limiter().wait_for(OutboundChannel::Public, &settings(2000, 6), now), Duration::ZERO);
+
+    }
+
+    #[test]
+    fn waits_out_the_minimum_interval() {
+
+        let mut limiter = limiter();
+        let now = Instant::now();
+        limiter.record(OutboundChannel::Public, now);
+
+        let settings = settings(2000, 0);
+        assert_eq!(limiter.wait_for(OutboundChannel::Public, &settings, now + Duration::from_millis(500)), Duration::from_millis(1500));
+        assert_eq!(limiter.wait_for(OutboundChannel::Public, &settings, now + Duration::from_millis(2500)), Duration::ZERO);
+
+    }
+
+    #[test]
+    fn waits_for_room_in_the_minute() {
+
+        let mut limiter = limiter();
+        let start = Instant::now();
+        for second in 0..3 {
+            limiter.record(OutboundChannel::Whisper, start + Duration::from_secs(second));
+        }
+
+        let settings = settings(0, 3);
+        let now = start + Duration::from_secs(10);
+        assert_eq!(limiter.wait_for(OutboundChannel::Whisper, &settings, now), Duration::from_secs(50));
+        assert_eq!(limiter.wait_for(OutboundChannel::Whisper, &settings, start + MINUTE), Duration::ZERO);
+
+    }
+
+    #[test]
+    fn channels_are_limited_separately() {
+
+        let mut limiter = limiter();
+        let now = Instant::now();
+        limiter.record(OutboundChannel::Public, now);
+
+        assert_eq!(limiter.wait_for(OutboundChannel::Group, &settings(2000, 1), now), Duration::ZERO);
+        assert!(!limiter.wait_for(OutboundChannel::Public, &settings(2000, 1), now).is_zero());
+
+    }
+
+}
@@ -0,0 +1,252 @@
+use super::game_input::{GameInput, Key};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    KeyDown(Key),
+    KeyDownBlocking(Key),
+    KeyUp(Key),
+    Char(char),
+    Wait(u64)
+}
+
+/// Keeps every call instead of sending it anywhere. Waits are recorded, not slept.
+#[derive(Default)]
+pub struct RecordingInput {
+    pub events: Vec<InputEvent>,
+    pub focused: bool
+}
+
+impl RecordingInput {
+
+    pub fn new() -> RecordingInput {
+
+        RecordingInput::default()
+
+    }
+
+    /// The text typed into chat, one entry per Enter that submitted it.
+    pub fn typed_lines(&self) -> Vec<String> {
+
+        let mut lines = Vec::new();
+        let mut line: Option<String> = None;
+        let mut shift = false;
+        for event in self.events.iter() {
+
+            match event {
+                InputEvent::KeyDown(Key::Shift) => shift = true,
+                InputEvent::KeyUp(Key::Shift) => shift = false,
+                // Shift+Enter is only used to close chat before posting.
+                InputEvent::KeyDown(Key::Enter) if shift => {},
+                InputEvent::KeyDown(Key::Enter) => match line.take() {
+                    Some(text) => lines.push(text),
+                    None => line = Some(String::new())
+                },
+                InputEvent::Char(c) => {
+                    if let Some(text) = line.as_mut() {
+                        text.push(*c);
+                    }
+                },
+                _ => {}
+            }
+
+        }
+
+        lines
+
+    }
+
+}
+
+impl GameInput for RecordingInput {
+
+    fn key_down(&mut self, key: Key) {
+
+        self.events.push(InputEvent::KeyDown(key));
+
+    }
+
+    fn key_down_blocking(&mut self, key: Key) {
+
+        self.events.push(InputEvent::KeyDownBlocking(key));
+
+    }
+
+    fn key_up(&mut self, key: Key) {
+
+        self.events.push(InputEvent::KeyUp(key));
+
+    }
+
+    fn char(&mut self, c: char) {
+
+        self.events.push(InputEvent::Char(c));
+
+    }
+
+    fn wait(&mut self, millis: u64) {
+
+        self.events.push(InputEvent::Wait(millis));
+
+    }
+
+    fn in_focus(&self) -> bool {
+
+        self.focused
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use super::super::{post_part, prep_game_for_input, split_into_parts};
+    use crate::dal::db::user_character_messages::{MessageType, UserCharacterMessages};
+    use crate::swtor::SwtorChannel;
+
+    fn chat_message(messages: &[&str]) -> UserCharacterMessages {
+
+        UserCharacterMessages {
+            message_type: MessageType::ChatMessage,
+            character_id: None,
+            messages: messages.iter().map(|message| message.to_string()).collect()
+        }
+
+    }
+
+    fn prep_events() -> Vec<InputEvent> {
+
+        let mut events = Vec::new();
+        for _ in 0..64 {
+            events.push(InputEvent::KeyDownBlocking(Key::Backspace));
+            events.push(InputEvent::Wait(2));
+        }
+
+        events.extend([
+            InputEvent::KeyDown(Key::Shift),
+            InputEvent::KeyDown(Key::Enter),
+            InputEvent::Wait(50),
+            InputEvent::KeyUp(Key::Enter),
+            InputEvent::KeyUp(Key::Shift),
+            InputEvent::Wait(50)
+        ]);
+        events
+
+    }
+
+    fn line_events(text: &str) -> Vec<InputEvent> {
+
+        let mut events = vec![InputEvent::KeyDown(Key::Enter), InputEvent::Wait(250)];
+        for c in text.chars() {
+            events.push(InputEvent::Char(c));
+            events.push(InputEvent::Wait(10));
+        }
+
+        events.push(InputEvent::KeyDown(Key::Enter));
+        events.push(InputEvent::Wait(20));
+        events
+
+    }
+
+    fn post(messages: &[&str]) -> RecordingInput {
+
+        let mut input = RecordingInput::new();
+        prep_game_for_input(&mut input);
+
+        for part in split_into_parts(false, &chat_message(messages)).unwrap().iter() {
+            post_part(&mut input, part).unwrap();
+        }
+
+        input
+
+    }
+
+    #[test]
+    fn prep_clears_and_closes_chat() {
+
+        let mut input = RecordingInput::new();
+        prep_game_for_input(&mut input);
+
+        assert_eq!(input.events, prep_events());
+        assert!(input.typed_lines().is_empty());
+
+    }
+
+    #[test]
+    fn types_a_slash_command() {
+
+        let input = post(&["/e hi"]);
+
+        let mut expected = prep_events();
+        expected.extend([
+            InputEvent::KeyDown(Key::Enter),
+            InputEvent::Wait(250),
+            InputEvent::Char('/'),
+            InputEvent::Wait(10),
+            InputEvent::Char('e'),
+            InputEvent::Wait(10),
+            InputEvent::Char(' '),
+            InputEvent::Wait(10),
+            InputEvent::Char('h'),
+            InputEvent::Wait(10),
+            InputEvent::Char('i'),
+            InputEvent::Wait(10),
+            InputEvent::KeyDown(Key::Enter),
+            InputEvent::Wait(20)
+        ]);
+
+        assert_eq!(input.events, expected);
+        assert_eq!(input.typed_lines(), vec!["/e hi".to_string()]);
+
+    }
+
+    #[test]
+    fn types_a_whisper() {
+
+        let input = post(&["/w Kira Carsen: Meet me at the cantina."]);
+
+        let mut expected = prep_events();
+        expected.extend(line_events("/w Kira Carsen: Meet me at the cantina."));
+
+        assert_eq!(input.events, expected);
+        assert_eq!(input.typed_lines(), vec!["/w Kira Carsen: Meet me at the cantina.".to_string()]);
+
+    }
+
+    #[test]
+    fn types_each_part_of_a_multi_part_post() {
+
+        let input = post(&["/s First part.", "/s Second part.", "Plain text."]);
+
+        let mut expected = prep_events();
+        expected.extend(line_events("/s First part."));
+        expected.extend(line_events("/s Second part."));
+        expected.extend(line_events("Plain text."));
+
+        assert_eq!(input.events, expected);
+        assert_eq!(input.typed_lines(), vec![
+            "/s First part.".to_string(),
+            "/s Second part.".to_string(),
+            "Plain text.".to_string()
+        ]);
+
+    }
+
+    #[test]
+    fn retried_whispers_expect_an_echo_to_the_recipient() {
+
+        let parts = split_into_parts(true, &chat_message(&["/w Kira: Hello there", "/roll 100"])).unwrap();
+
+        let whisper = parts[0].confirm.as_ref().unwrap();
+        assert_eq!(parts[0].text, "/w Kira: Hello there");
+        assert!(whisper.channels == vec![SwtorChannel::WHISPER]);
+        assert_eq!(whisper.to.as_deref(), Some("Kira"));
+        assert_eq!(whisper.text, "Hello there");
+
+        // Rolls are never echoed as typed, so they aren't waited on.
+        assert!(parts[1].confirm.is_none());
+
+    }
+
+}
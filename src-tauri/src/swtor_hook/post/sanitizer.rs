@@ -0,0 +1,218 @@
+/*
+    The game only renders printable ASCII and Latin-1, so anything else is either transliterated into
+    something it can show or dropped before it is typed. Accented letters are spelled plainly too, Latin-1
+    ones included, so a name reads the same however it was pasted in. The user's own replacements are
+    applied first, longest first, and may map whole strings, the built in table below covers single characters.
+*/
+
+use serde::Serialize;
+
+use crate::dal::db::settings::chat_settings::sanitizer::SanitizerSettings;
+
+/// Each entry maps every character in the first string to the second.
+const TRANSLITERATIONS: &[(&str, &str)] = &[
+
+    // Punctuation word processors like to substitute
+    ("‘’‚‛′", "'"),
+    ("“”„‟″", "\""),
+    ("‐‑‒–—―−", "-"),
+    ("…", "..."),
+    ("•", "*"),
+    ("€", "EUR"),
+    ("™", "TM"),
+    ("\t\n\r", " "),
+
+    // Invisible characters, mostly left behind by copying from elsewhere
+    ("\u{200B}\u{200C}\u{200D}\u{2060}\u{FEFF}\u{FE0E}\u{FE0F}", ""),
+
+    // Latin-1 letters
+    ("ÀÁÂÃÄÅ", "A"), ("àáâãäå", "a"),
+    ("Æ", "AE"), ("æ", "ae"),
+    ("Ç", "C"), ("ç", "c"),
+    ("Ð", "D"), ("ð", "d"),
+    ("ÈÉÊË", "E"), ("èéêë", "e"),
+    ("ÌÍÎÏ", "I"), ("ìíîï", "i"),
+    ("Ñ", "N"), ("ñ", "n"),
+    ("ÒÓÔÕÖØ", "O"), ("òóôõöø", "o"),
+    ("ÙÚÛÜ", "U"), ("ùúûü", "u"),
+    ("Ý", "Y"), ("ýÿ", "y"),
+    ("Þ", "Th"), ("þ", "th"),
+    ("ß", "ss"),
+
+    // Latin Extended-A
+    ("ĀĂĄ", "A"), ("āăą", "a"),
+    ("ĆĈĊČ", "C"), ("ćĉċč", "c"),
+    ("ĎĐ", "D"), ("ďđ", "d"),
+    ("ĒĔĖĘĚ", "E"), ("ēĕėęě", "e"),
+    ("ĜĞĠĢ", "G"), ("ĝğġģ", "g"),
+    ("ĤĦ", "H"), ("ĥħ", "h"),
+    ("ĨĪĬĮİ", "I"), ("ĩīĭįı", "i"),
+    ("Ĳ", "IJ"), ("ĳ", "ij"),
+    ("Ĵ", "J"), ("ĵ", "j"),
+    ("Ķ", "K"), ("ķĸ", "k"),
+    ("ĹĻĽĿŁ", "L"), ("ĺļľŀł", "l"),
+    ("ŃŅŇŊ", "N"), ("ńņňŉŋ", "n"),
+    ("ŌŎŐ", "O"), ("ōŏő", "o"),
+    ("Œ", "OE"), ("œ", "oe"),
+    ("ŔŖŘ", "R"), ("ŕŗř", "r"),
+    ("ŚŜŞŠ", "S"), ("śŝşšſ", "s"),
+    ("ŢŤŦ", "T"), ("ţťŧ", "t"),
+    ("ŨŪŬŮŰŲ", "U"), ("ũūŭůűų", "u"),
+    ("Ŵ", "W"), ("ŵ", "w"),
+    ("ŶŸ", "Y"), ("ŷ", "y"),
+    ("ŹŻŽ", "Z"), ("źżž", "z"),
+
+    // Emoji, as the emoticons they replaced
+    ("🙂😊☺", ":)"),
+    ("😀😃😄😁", ":D"),
+    ("😉", ";)"),
+    ("😛😜😝", ":P"),
+    ("🙁☹😞😟", ":("),
+    ("😢😭", ":'("),
+    ("😮😲", ":O"),
+    ("😂🤣", "XD"),
+    ("❤💕💖💗", "<3"),
+    ("👍", "(y)")
+
+];
+
+/// A character or string that won't be typed as written.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Replacement {
+    pub from: String,
+    pub to: String
+}
+
+pub struct Sanitized {
+    pub text: String,
+    pub replacements: Vec<Replacement>
+}
+
+pub fn is_supported(c: char) -> bool {
+
+    matches!(c as u32, 0x20..=0x7E | 0xA0..=0xFF)
+
+}
+
+fn transliterate(c: char) -> Option<&'static str> {
+
+    TRANSLITERATIONS
+        .iter()
+        .find(|(from, _)| from.contains(c))
+        .map(|(_, to)| *to)
+
+}
+
+pub fn sanitize(text: &str, settings: &SanitizerSettings) -> Sanitized {
+
+    let mut replacements: Vec<Replacement> = Vec::new();
+    let mut note = |from: &str, to: &str| {
+
+        let replacement = Replacement { from: from.to_string(), to: to.to_string() };
+        if !replacements.contains(&replacement) {
+            replacements.push(replacement);
+        }
+
+    };
+
+    if !settings.enabled {
+        return Sanitized { text: text.to_string(), replacements };
+    }
+
+    // One pass, longest key first at each position, so neither a shorter key nor an earlier replacement's
+    // output can cut into a longer match.
+    let mut user_replacements: Vec<(&String, &String)> = settings.replacements
+        .iter()
+        .filter(|(from, _)| !from.is_empty())
+        .collect();
+    user_replacements.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+
+    let mut text_replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+
+        match user_replacements.iter().find(|(from, _)| rest.starts_with(from.as_str())) {
+            Some((from, to)) => {
+                text_replaced.push_str(to);
+                note(from, to);
+                rest = &rest[from.len()..];
+            },
+            None => {
+                text_replaced.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+
+    }
+    let text = text_replaced;
+
+    if settings.pass_through_unicode {
+        return Sanitized { text, replacements };
+    }
+
+    let mut sanitized = String::with_capacity(text.len());
+    for c in text.chars() {
+
+        // The table comes first, as it covers Latin-1 letters the game could otherwise show as they are.
+        let to = match transliterate(c) {
+            Some(to) => to,
+            None if is_supported(c) => {
+                sanitized.push(c);
+                continue;
+            },
+            None => settings.unsupported_replacement.as_str()
+        };
+
+        sanitized.push_str(to);
+        note(&c.to_string(), to);
+
+    }
+
+    Sanitized { text: sanitized, replacements }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn settings(replacements: &[(&str, &str)]) -> SanitizerSettings {
+
+        SanitizerSettings {
+            replacements: replacements
+                .iter()
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+            ..SanitizerSettings::default()
+        }
+
+    }
+
+    #[test]
+    fn longer_replacements_win_over_shorter_ones() {
+
+        let sanitized = sanitize("brb, afk", &settings(&[("b", "B"), ("brb", "be right back")]));
+        assert_eq!(sanitized.text, "be right back, afk");
+
+    }
+
+    #[test]
+    fn transliterates_latin_1_letters() {
+
+        let sanitized = sanitize("Café Straße, Ærøskøbing", &settings(&[]));
+        assert_eq!(sanitized.text, "Cafe Strasse, AEroskobing");
+        assert!(sanitized.replacements.contains(&Replacement { from: "é".to_string(), to: "e".to_string() }));
+
+    }
+
+    #[test]
+    fn keeps_latin_1_symbols() {
+
+        let sanitized = sanitize("£5 × 2 ± ½", &settings(&[]));
+        assert_eq!(sanitized.text, "£5 × 2 ± ½");
+        assert!(sanitized.replacements.is_empty());
+
+    }
+
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::prelude::*;
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::dal::db::scheduled_post::ScheduledPost;
+use crate::dal::db::settings;
+use crate::dal::db::user_character_messages::UserCharacterMessages;
+
+use super::post_queue;
+
+/// Emitted as scheduled_post_started once a scheduled post has been handed to the post queue.
+#[derive(Serialize, Clone)]
+pub struct ScheduledPostStarted {
+    pub scheduled_post_id: i32,
+    pub post_id: u64
+}
+
+lazy_static! {
+    /// Scheduled posts waiting in the post queue, and the post id each was queued as.
+    static ref IN_QUEUE: Arc<Mutex<HashMap<i32, u64>>> = Arc::new(Mutex::new(HashMap::new()));
+}
+
+/*
+    Schedules live in the database, so anything still pending when ChaTOR closes is picked up again on the
+    next start. Due posts go through the post queue like any other, so they wait their turn behind a post
+    that's being typed, and their rows are only deleted once the queue is done with them. One that was
+    still queued when ChaTOR closed is queued again on the next start.
+*/
+pub fn start_scheduler(window: tauri::Window) {
+
+    thread::spawn(move || {
+
+        loop {
+
+            // Held throughout, so a post cancelled while this runs is either never queued or cancelled in the queue.
+            let mut in_queue = IN_QUEUE.lock().unwrap();
+            match ScheduledPost::get_all() {
+                Ok(scheduled_posts) => {
+
+                    let now = Utc::now();
+                    let due: Vec<ScheduledPost> = scheduled_posts
+                        .into_iter()
+                        .filter(|post| post.post_at <= now && !in_queue.contains_key(&post.scheduled_post_id))
+                        .collect();
+
+                    for scheduled_post in due {
+                        start_scheduled_post(&window, scheduled_post, now, &mut in_queue);
+                    }
+
+                },
+                Err(err) => {
+                    error!("{}", err);
+                }
+            }
+            drop(in_queue);
+
+            thread::sleep(Duration::from_secs(1));
+
+        }
+
+    });
+
+}
+
+fn start_scheduled_post(window: &tauri::Window, scheduled_post: ScheduledPost, now: DateTime<Utc>, in_queue: &mut HashMap<i32, u64>) {
+
+    let scheduled_post_id = scheduled_post.scheduled_post_id;
+    let missed_after_secs = i64::try_from(settings::get_settings().chat.missed_schedule_after_secs).unwrap_or(i64::MAX);
+    if (now - scheduled_post.post_at).num_seconds() > missed_after_secs {
+
+        if let Err(err) = ScheduledPost::delete(scheduled_post_id) {
+            error!("{}", err);
+            return;
+        }
+
+        warn!("Missed scheduled post {} for {}", scheduled_post_id, scheduled_post.post_at);
+        window.emit("scheduled_post_missed", scheduled_post).unwrap();
+        return;
+
+    }
+
+    let (post_id, finished) = post_queue::enqueue(window.clone(), scheduled_post.retry, scheduled_post.character_message);
+    in_queue.insert(scheduled_post_id, post_id);
+    info!("Scheduled post {} queued as post {}", scheduled_post_id, post_id);
+    window.emit("scheduled_post_started", ScheduledPostStarted { scheduled_post_id, post_id }).unwrap();
+
+    thread::spawn(move || {
+
+        // Posted, failed or cancelled, it has had its turn either way.
+        if let Ok(Err(err)) = finished.recv() {
+            warn!("Scheduled post {} did not post: {}", scheduled_post_id, err);
+        }
+
+        if let Err(err) = ScheduledPost::delete(scheduled_post_id) {
+            error!("{}", err);
+        }
+        IN_QUEUE.lock().unwrap().remove(&scheduled_post_id);
+
+    });
+
+}
+
+/// A post found more than the missed_schedule_after_secs setting past its time is dropped, not posted.
+#[tauri::command]
+pub fn schedule_post(post_at: DateTime<Utc>, retry: bool, character_message: UserCharacterMessages) -> Result<ScheduledPost, &'static str> {
+
+    ScheduledPost::new(post_at, retry, character_message)
+
+}
+
+/// Like schedule_post, `delay_secs` from now.
+#[tauri::command]
+pub fn schedule_post_after(delay_secs: u64, retry: bool, character_message: UserCharacterMessages) -> Result<ScheduledPost, &'static str> {
+
+    let post_at = i64::try_from(delay_secs)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .and_then(|delay| Utc::now().checked_add_signed(delay))
+        .ok_or("Delay is too long")?;
+
+    ScheduledPost::new(post_at, retry, character_message)
+
+}
+
+#[tauri::command]
+pub fn get_scheduled_posts() -> Result<Vec<ScheduledPost>, &'static str> {
+
+    ScheduledPost::get_all()
+
+}
+
+/// Also cancels the post if it has already been handed to the post queue.
+#[tauri::command]
+pub fn cancel_scheduled_post(scheduled_post_id: i32) -> Result<(), &'static str> {
+
+    let in_queue = IN_QUEUE.lock().unwrap();
+    if let Some(post_id) = in_queue.get(&scheduled_post_id) {
+        return post_queue::cancel(*post_id);
+    }
+
+    match ScheduledPost::delete(scheduled_post_id)? {
+        true => Ok(()),
+        false => Err("Scheduled post not found")
+    }
+
+}
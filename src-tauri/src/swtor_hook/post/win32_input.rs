@@ -0,0 +1,92 @@
+use std::thread;
+use std::time::Duration;
+
+use windows::Win32::Foundation::{LPARAM, WPARAM};
+use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, SendMessageW, WM_CHAR, WM_KEYDOWN, WM_KEYUP};
+
+use crate::swtor_hook;
+
+use super::game_input::{GameInput, Key};
+
+/// Sends input to the hooked SWTOR window. Does nothing while the game isn't running.
+pub struct Win32Input;
+
+impl Win32Input {
+
+    pub fn new() -> Win32Input {
+
+        Win32Input
+
+    }
+
+    fn post_message(&self, msg_type: u32, wparam: usize) {
+
+        if let Some(hwnd) = swtor_hook::get_hwnd() {
+
+            unsafe {
+                let _ = PostMessageW(hwnd, msg_type, WPARAM(wparam), LPARAM(0));
+            }
+
+        }
+
+    }
+
+    fn send_message(&self, msg_type: u32, wparam: usize) {
+
+        if let Some(hwnd) = swtor_hook::get_hwnd() {
+
+            unsafe {
+                let _ = SendMessageW(hwnd, msg_type, WPARAM(wparam), LPARAM(0));
+            }
+
+        }
+
+    }
+
+}
+
+impl GameInput for Win32Input {
+
+    fn key_down(&mut self, key: Key) {
+
+        self.post_message(WM_KEYDOWN, key.virtual_key_code());
+
+    }
+
+    fn key_down_blocking(&mut self, key: Key) {
+
+        self.send_message(WM_KEYDOWN, key.virtual_key_code());
+
+    }
+
+    fn key_up(&mut self, key: Key) {
+
+        self.post_message(WM_KEYUP, key.virtual_key_code());
+
+    }
+
+    /// Characters outside the BMP go as a surrogate pair, one WM_CHAR per half, as Windows itself sends them.
+    fn char(&mut self, c: char) {
+
+        let mut units = [0u16; 2];
+        for unit in c.encode_utf16(&mut units).iter() {
+            self.post_message(WM_CHAR, *unit as usize);
+        }
+
+    }
+
+    fn wait(&mut self, millis: u64) {
+
+        if millis > 0 {
+            thread::sleep(Duration::from_millis(millis));
+        }
+
+    }
+
+    fn in_focus(&self) -> bool {
+
+        swtor_hook::window_in_focus()
+
+    }
+
+}